@@ -1,5 +1,5 @@
+use std::convert::TryFrom;
 use std::io;
-use std::iter::repeat;
 use std::time::{self, SystemTime, UNIX_EPOCH};
 use std::thread;
 use serde_json::json;
@@ -19,14 +19,35 @@ struct Cli {
 static COMMANDS: &[(&str, &str)] = &[
     ("help",             "Displays this the help page"),
     ("join_game",        "Set the game to make moves against, usage: join_game <game_address>"),
-    ("new_game",         "Create a new game to play with an opponent, usage: new_game <opponent_address>"),
+    ("new_game",         "Create a new game to play with one or more opponents, usage: new_game <opponent_address>[,<opponent_address>...] <game_type> <settings_json>"),
+    ("new_timed_game",   "Create a new game played with a time control, usage: new_timed_game <opponent_address>[,<opponent_address>...] <game_type> <time_control_json> <settings_json>"),
+    ("new_team_game",    "Create a new game between teams, usage: new_team_game <game_type> <teams_json> <settings_json>"),
+    ("results",          "Display how this game ended for each team or player"),
+    ("integrity",        "Display any forks in the moves of this game and players who moved twice"),
+    ("game_types",       "Display the types of game that can be played"),
     ("moves",            "Display the set of moves this game supports"),
+    ("pgn",              "Display this chess game in Portable Game Notation"),
+    ("sgf",              "Display this go game in Smart Game Format"),
     ("make_move",        "Make a move in this game, usage: make_move <move_json>"),
+    ("abandon",          "Abandon this game before any move has been played"),
+    ("resign",           "Resign from this game"),
+    ("claim_timeout",    "Claim the win once your opponent has run out of time"),
+    ("claim_forfeit",    "Claim the win when your opponent has made two moves at once, see the integrity command"),
+    ("offer_draw",       "Offer your opponent a draw"),
+    ("accept_draw",      "Accept the draw your opponent offered"),
+    ("decline_draw",     "Decline the draw your opponent offered"),
+    ("commit",           "Commit to secret data without revealing it, usage: commit <key> <salt> <data_json>"),
+    ("reveal",           "Reveal the data behind a commitment, usage: reveal <key> <salt> <data_json>"),
+    ("request_roll",     "Commit to a random seed for shared dice, usage: request_roll <key> <salt> <seed>"),
+    ("reveal_seed",      "Reveal your seed once every player has committed, usage: reveal_seed <key> <salt> <seed>"),
+    ("seal",             "Seal your move for this round of a simultaneous game, usage: seal <salt> <move_json>"),
+    ("reveal_move",      "Reveal your sealed move once every player has sealed one, usage: reveal_move <salt> <move_json>"),
  
-    ("create_proposal",  "Publicly publish that you are looking for someone to play with. Usage: post_propoal <message>"),
-    ("accept_proposal",   "Accept a propsal. This will start a new game. Usage: accept_proposal <proposal_hash>"),
-    ("get_proposals",    "Get all of the public proposals that are current"),
+    ("create_proposal",  "Publicly publish that you are looking for someone to play with. Usage: create_proposal <game_type> <terms_json> <message>, where the terms can hold settings, time_control, rated, first_mover (Proposer or Acceptor) and ttl, the seconds until the proposal expires"),
+    ("accept_proposal",   "Accept a propsal. This creates a game the proposer still has to confirm. Usage: accept_proposal <proposal_hash> [<other_player_address>,...]"),
+    ("get_proposals",    "Get all of the public proposals that are current, usage: get_proposals [<filter_json>]"),
     ("check_responses",  "Given a proposal hash find the responses. Usage: check_responses <proposal_hash>"),
+    ("confirm_response", "Confirm the game you will play in response to your proposal. Usage: confirm_response <proposal_hash> <game_hash>"),
     ("remove_proposal",  "Remove a proposal that you authored given its hash. Usage: remove_proposal <proposal_hash>"),    
  
     ("exit",             "Exit this CLI. Holochain will persist state so games can be resumed later."),
@@ -38,30 +59,39 @@ fn main() -> io::Result<()> {
     // create the functions required for playing the game
     let whoami = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "whoami".into());
     let valid_moves = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "get_valid_moves".into());
+    let game_types = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "get_game_types".into());
     let make_move = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "make_move".into());
+    let hash_secret = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "hash_secret".into());
+    let hash_move = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "hash_move".into());
     let create_game = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "create_game".into());
+    let create_team_game = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "create_team_game".into());
+    let get_team_results = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "get_team_results".into());
+    let get_game_integrity = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "get_game_integrity".into());
     let render_game = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "render_state".into());
+    let get_pgn = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "get_pgn".into());
+    let get_sgf = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "get_sgf".into());
 
     // matchmaking funcs
     let create_proposal = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "create_proposal".into());
     let get_proposals = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "get_proposals".into());
     let accept_proposal = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "accept_proposal".into());
     let check_responses = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "check_responses".into());
-    let _remove_proposal = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "remove_proposal".into());
+    let confirm_response = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "confirm_response".into());
+    let remove_proposal = holochain_call_generator(cli.url.clone(), cli.instance.clone(), "main".into(), "remove_proposal".into());
    
 
     let interface = Interface::new("Holochain generic game")?;
 
-    println!("");
-    println!("");
-    println!("{}", repeat('#').take(70).collect::<String>());
+    println!();
+    println!();
+    println!("{}", "#".repeat(70));
     println!("CLI interface for games written using the Holochain Generic Game framework.");
     println!("Enter \"help\" for a list of commands.");
     println!("Use \"create_game <agent_id>\" or \"join_game <game_address>\" to start or join a game.");
     println!("Press Ctrl-D or enter \"quit\" to exit.");
-    println!("{}", repeat('#').take(70).collect::<String>());
-    println!("");
-    println!("");
+    println!("{}", "#".repeat(70));
+    println!();
+    println!();
 
     match whoami(json!({})) {
     	Ok(agent_addr) => {
@@ -73,8 +103,8 @@ fn main() -> io::Result<()> {
     	}
     }
 
-    println!("");
-    println!("");
+    println!();
+    println!();
 
 	interface.set_prompt("No game> ")?;
 
@@ -108,46 +138,174 @@ fn main() -> io::Result<()> {
             		Err("argument must be a valid address".into())
             	}
             }
-            "new_game" => {
-            	if is_agent_addr(args) {
+            "new_game" | "new_timed_game" => {
+                let (opponents, args) = split_first_word(args);
+                let (game_type, args) = split_first_word(args);
+                // the time control json can't contain spaces, e.g. {"Total":{"seconds":600,"increment":5}}
+                let (time_control, settings) = if cmd == "new_timed_game" { split_first_word(args) } else { ("", args) };
+                let opponents = split_addresses(opponents);
+            	if !opponents.is_empty() && opponents.iter().all(|opponent| is_agent_addr(opponent)) {
             		let result = create_game(json!({
-            			"opponent": args,
-            			"timestamp": current_timestamp()
+            			"opponents": opponents,
+            			"timestamp": current_timestamp(),
+            			"game_type": game_type_or_default(game_type),
+            			"settings": serde_json::from_str::<serde_json::Value>(settings).unwrap_or(serde_json::Value::Null),
+            			"time_control": serde_json::from_str::<serde_json::Value>(time_control).unwrap_or(serde_json::Value::Null)
             		}));
                     result.map(|result| {
                         current_game = result.as_str().map(|s| s.to_string());
                     })
             	} else {
-            		Err("argument must be valid agent address of an opponent.".into())
+            		Err("argument must be a comma separated list of valid agent addresses of the opponents.".into())
             	}
             }
-            "moves" => {
-            	valid_moves(json!({})).map(|result| {
-	            	println!("The valid moves are:");
-	            	result.as_array().unwrap()
-	            	.iter()
-	            	.for_each(|elem| {
-	            		println!("- {}", elem);
-	            	});
+            "new_team_game" => {
+                // the teams json can't contain spaces, e.g. [{"name":"red","members":["Hc..","Hc.."]},...]
+                let (game_type, args) = split_first_word(args);
+                let (teams, settings) = split_first_word(args);
+                match serde_json::from_str::<serde_json::Value>(teams) {
+                    Ok(teams) => create_team_game(json!({
+                        "teams": teams,
+                        "timestamp": current_timestamp(),
+                        "game_type": game_type_or_default(game_type),
+                        "settings": serde_json::from_str::<serde_json::Value>(settings).unwrap_or(serde_json::Value::Null)
+                    })).map(|result| {
+                        current_game = result.as_str().map(|s| s.to_string());
+                    }),
+                    Err(e) => Err(format!("teams must be a JSON list of teams: {}", e)),
+                }
+            },
+            "results" => {
+                if let Some(current_game) = current_game.clone() {
+                    get_team_results(json!({"game_address": current_game})).map(|results| {
+                        results.as_array().unwrap().iter().for_each(|result| {
+                            println!("{}: {} {}", result["team"], result["result"], result["members"]);
+                        });
+                    })
+                } else {
+                    Err("No game set. use the \"join_game\" command.".into())
+                }
+            },
+            "integrity" => {
+                if let Some(current_game) = current_game.clone() {
+                    get_game_integrity(json!({"game_address": current_game})).map(|integrity| {
+                        let forks = integrity["forks"].as_array().cloned().unwrap_or_default();
+                        if forks.is_empty() {
+                            println!("The moves of this game form a single chain");
+                        }
+                        forks.iter().for_each(|fork| {
+                            println!("Fork after {}: following {}, discarded {}", fork["base"], fork["canonical"], fork["discarded"]);
+                        });
+                        integrity["double_moves"].as_array().cloned().unwrap_or_default().iter().for_each(|double_move| {
+                            println!("{} made {} moves after {}", double_move["author"], double_move["moves"].as_array().map_or(0, |moves| moves.len()), double_move["base"]);
+                        });
+                    })
+                } else {
+                    Err("No game set. use the \"join_game\" command.".into())
+                }
+            },
+            "game_types" => {
+                game_types(json!({})).map(|result| {
+                    println!("The game types are:");
+                    result.as_array().unwrap()
+                    .iter()
+                    .for_each(|elem| {
+                        println!("- {}", elem);
+                    });
                     println!();
-            	})
+                })
+            },
+            "moves" => {
+                if let Some(current_game) = current_game.clone() {
+                	valid_moves(json!({"game_address": current_game})).map(|result| {
+    	            	println!("The valid moves are:");
+    	            	result.as_array().unwrap()
+    	            	.iter()
+    	            	.for_each(|elem| {
+    	            		println!("- {}", elem);
+    	            	});
+                        println!();
+                	})
+                } else {
+                    Err("No game set to list moves for. use the \"join_game\" command.".into())
+                }
+            },
+            "pgn" | "sgf" => {
+                if let Some(current_game) = current_game.clone() {
+                    let export = if cmd == "pgn" { &get_pgn } else { &get_sgf };
+                    export(json!({"game_address": current_game})).map(|result| {
+                        println!("{}", result.as_str().unwrap_or_default());
+                    })
+                } else {
+                    Err("No game set to export. use the \"join_game\" command.".into())
+                }
             },
-            "make_move" => {
+            "make_move" | "abandon" | "resign" | "claim_timeout" | "claim_forfeit" | "offer_draw" | "accept_draw" | "decline_draw" | "commit" | "reveal" | "request_roll" | "reveal_seed" | "seal" | "reveal_move" => {
             	if let Some(current_game) = current_game.clone() {
-            		let move_json: serde_json::Value = serde_json::from_str(args).unwrap_or(serde_json::Value::Null);
-	            	println!("making move: {:?}", args);
-	            	make_move(json!({
-		            	"new_move": {
-		            		"game": current_game,
-		            		"move_type": move_json,
-		            		"timestamp": current_timestamp()
-		            	}
-	            	})).map(|_| {
-                        println!("Move cast successfully");
-                        println!("Waiting for gossip...");
-                        // wait a bit so it displays correctly
-                        thread::sleep(time::Duration::from_millis(4000));
-                        println!("OK!")
+            		let move_json: Result<serde_json::Value, String> = match cmd {
+                        "commit" | "reveal" => {
+                            // keep the salt secret until the reveal, otherwise the data can be guessed from the commitment
+                            let (key, args) = split_first_word(args);
+                            let (salt, data) = split_first_word(args);
+                            let data = serde_json::from_str::<serde_json::Value>(data).unwrap_or(serde_json::Value::Null);
+                            if cmd == "commit" {
+                                hash_secret(json!({"key": key, "data": data, "salt": salt}))
+                                    .map(|commitment| json!({"Commit": {"key": key, "commitment": commitment}}))
+                            } else {
+                                Ok(json!({"Reveal": {"key": key, "data": data, "salt": salt}}))
+                            }
+                        },
+                        "request_roll" | "reveal_seed" => {
+                            let (key, args) = split_first_word(args);
+                            let (salt, seed) = split_first_word(args);
+                            if cmd == "request_roll" {
+                                hash_secret(json!({"key": key, "data": seed, "salt": salt}))
+                                    .map(|commitment| json!({"RandomnessRequest": {"key": key, "commitment": commitment}}))
+                            } else {
+                                Ok(json!({"RandomnessReveal": {"key": key, "seed": seed, "salt": salt}}))
+                            }
+                        },
+                        "seal" | "reveal_move" => {
+                            let (salt, move_type) = split_first_word(args);
+                            let move_type = serde_json::from_str::<serde_json::Value>(move_type).unwrap_or(serde_json::Value::Null);
+                            if cmd == "seal" {
+                                hash_move(json!({"game_address": current_game, "move_type": move_type, "salt": salt}))
+                                    .map(|commitment| json!({"SealMove": {"commitment": commitment}}))
+                            } else {
+                                Ok(json!({"RevealMove": {"move_type": move_type, "salt": salt}}))
+                            }
+                        },
+                        "abandon" => Ok(json!("Abandon")),
+                        "resign" => Ok(json!("Resign")),
+                        "claim_timeout" => Ok(json!("ClaimTimeout")),
+                        "claim_forfeit" => {
+                            whoami(json!({})).and_then(|me| get_game_integrity(json!({"game_address": current_game})).and_then(|integrity| {
+                                integrity["double_moves"].as_array().cloned().unwrap_or_default().into_iter()
+                                    .find(|double_move| double_move["author"] != me)
+                                    .map(|evidence| json!({"ClaimForfeit": {"evidence": evidence}}))
+                                    .ok_or_else(|| "Your opponent has not made a double move.".to_string())
+                            }))
+                        },
+                        "offer_draw" => Ok(json!("OfferDraw")),
+                        "accept_draw" => Ok(json!("AcceptDraw")),
+                        "decline_draw" => Ok(json!("DeclineDraw")),
+                        _ => Ok(serde_json::from_str(args).unwrap_or(serde_json::Value::Null)),
+                    };
+                    move_json.and_then(|move_json| {
+    	            	println!("making move: {}", move_json);
+    	            	make_move(json!({
+    		            	"new_move": {
+    		            		"game": current_game,
+    		            		"move_type": move_json,
+    		            		"timestamp": current_timestamp()
+    		            	}
+    	            	})).map(|_| {
+                            println!("Move cast successfully");
+                            println!("Waiting for gossip...");
+                            // wait a bit so it displays correctly
+                            thread::sleep(time::Duration::from_millis(4000));
+                            println!("OK!")
+                        })
                     })
                 }
             	else {
@@ -155,22 +313,45 @@ fn main() -> io::Result<()> {
             	}
             },
             "create_proposal" => {
-                println!("creating proposal with message {:?}", args);
-                let result = create_proposal(json!({"message": args}));
-                println!("Create result: {:?}", result);
-                Ok(())
+                let (game_type, args) = split_first_word(args);
+                let (terms, message) = split_first_word(args);
+                println!("creating proposal with message {:?}", message);
+                let mut params = serde_json::from_str::<serde_json::Value>(terms).unwrap_or(json!({}));
+                params["message"] = json!(message);
+                params["game_type"] = json!(game_type_or_default(game_type));
+                let ttl = params["ttl"].as_u64();
+                let expires_at = ttl
+                    .and_then(|ttl| u32::try_from(ttl).ok())
+                    .and_then(|ttl| current_timestamp().checked_add(ttl));
+                if ttl.is_some() && expires_at.is_none() {
+                    Err("ttl is too large, the proposal would expire later than a timestamp can hold.".into())
+                } else {
+                    if let Some(expires_at) = expires_at {
+                        params["expires_at"] = json!(expires_at);
+                    }
+                    let result = create_proposal(params);
+                    println!("Create result: {:?}", result);
+                    Ok(())
+                }
             },
             "get_proposals" => {
-                let result = get_proposals(json!({})).unwrap();
+                let filter = serde_json::from_str::<serde_json::Value>(args).unwrap_or(serde_json::Value::Null);
+                let result = get_proposals(json!({"filter": filter, "now": current_timestamp()})).unwrap();
                 println!("Current game proposals: \n");
                 result.as_array().unwrap().iter().for_each(|r| {
                     println!("[{}] : {{ Agent: {}, Message: {} }}", r["address"].as_str().unwrap(), r["entry"]["agent"], r["entry"]["message"]);
+                    println!("    {} {} rated: {}, time control: {}, first mover: {}, expires at: {}", r["entry"]["game_type"], r["entry"]["settings"], r["entry"]["rated"], r["entry"]["time_control"], r["entry"]["first_mover"], r["entry"]["expires_at"]);
                 });
                 println!("\n");
                 Ok(())
             },
             "accept_proposal" => {
-                accept_proposal(json!({"proposal_addr": args, "created_at": current_timestamp()})).map(|game_addr| {
+                let (proposal_addr, other_players) = split_first_word(args);
+                accept_proposal(json!({
+                    "proposal_addr": proposal_addr,
+                    "created_at": current_timestamp(),
+                    "other_players": split_addresses(other_players)
+                })).map(|game_addr| {
                     println!("Proposal accepted. Game created with address: {}", game_addr);
                     current_game = Some(game_addr.as_str().unwrap().into());
                 })
@@ -179,17 +360,22 @@ fn main() -> io::Result<()> {
                 let result = check_responses(json!({"proposal_addr": args})).unwrap();
                 println!("Proposal has the following responses: \n");
                 result.as_array().unwrap().iter().for_each(|response| {
-                    println!("[{}] : Agent: {}", response["address"], response["entry"]["player_1"]);
+                    println!("[{}] : Players: {} ({})", response["address"], response["entry"]["players"], response["status"]);
                 });
-                println!("use \"join_game\" with any of the listed addresses to join: \n");
+                println!("use \"confirm_response\" with one of the listed addresses to start playing it: \n");
                 Ok(())            
             },
+            "confirm_response" => {
+                let (proposal_addr, game_addr) = split_first_word(args);
+                confirm_response(json!({"proposal_addr": proposal_addr, "game_addr": game_addr})).map(|result| {
+                    current_game = result.as_str().map(|s| s.to_string());
+                    println!("Confirmed, you are now playing this game\n");
+                })
+            },
             "remove_proposal" => {
-                println!("NOT IMPLEMENTED - this has been disabled until deletion but is fixed.");
-                Ok(())
-                // remove_proposal(json!({"proposal_addr": args})).map(|_| {
-                //     println!("Proposal successfully marked as deleted\n");
-                // })
+                remove_proposal(json!({"proposal_addr": args})).map(|_| {
+                    println!("Proposal successfully marked as deleted\n");
+                })
             },
             "exit" => {
             	if let Some(current_game) = current_game.clone() {
@@ -259,15 +445,15 @@ fn holochain_call_generator(
 		    	r["result"].clone()
 		    })
 		    .map(|s| serde_json::from_str(
-                s.as_str().expect(&format!("Holochain did not return a string result: {}", s))
-            ).expect(&format!("Holochain did not return a valid stringified JSON result: {}", s)))
+                s.as_str().unwrap_or_else(|| panic!("Holochain did not return a string result: {}", s))
+            ).unwrap_or_else(|_| panic!("Holochain did not return a valid stringified JSON result: {}", s)))
 		    .map_err(|e| e.to_string())?;
 
 		// deal with the json encoded holochain error responses
 		if let Some(inner_result) = call_result.get("Ok") {
 			Ok(inner_result.clone())
 		} else {
-			Err(call_result["Err"].to_string())
+			Err(describe_error(&call_result["Err"]))
 		}
 	})
 
@@ -277,6 +463,18 @@ fn holochain_call_generator(
 =            Helpers            =
 ===============================*/
 
+/// Game errors come as JSON with a code inside an Internal error, show those as "CODE: message (address)"
+fn describe_error(error: &serde_json::Value) -> String {
+    error["Internal"].as_str()
+        .and_then(|internal| serde_json::from_str::<serde_json::Value>(internal).ok())
+        .filter(|game_error| game_error["code"].is_string())
+        .map(|game_error| format!("{}: {} ({})",
+            game_error["code"].as_str().unwrap_or_default(),
+            game_error["message"].as_str().unwrap_or_default(),
+            game_error["address"].as_str().unwrap_or_default()))
+        .unwrap_or_else(|| error.to_string())
+}
+
 fn split_first_word(s: &str) -> (&str, &str) {
     let s = s.trim();
 
@@ -286,6 +484,10 @@ fn split_first_word(s: &str) -> (&str, &str) {
     }
 }
 
+fn split_addresses(s: &str) -> Vec<&str> {
+    s.split(',').map(|address| address.trim()).filter(|address| !address.is_empty()).collect()
+}
+
 fn is_hash(s: &str) -> bool {
 	s.starts_with("Qm") && s.len() == 46
 }
@@ -294,6 +496,10 @@ fn is_agent_addr(s: &str) -> bool {
 	s.starts_with("Hc") && s.len() == 63
 }
 
+fn game_type_or_default(s: &str) -> &str {
+    if s.is_empty() { "TicTacToe" } else { s }
+}
+
 fn current_timestamp() -> u32 {
 	SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as u32
 }
@@ -0,0 +1,125 @@
+const {results, lastResult, makeMove, createGame, renderState, getState} = require('./helpers')
+
+// a 4x4 board with a single ship of two squares
+const settings = { board_size: 4, fleet: [2] }
+const alice_fleet = [{ x: 0, y: 0, length: 2, orientation: "Horizontal" }]
+const bob_fleet = [{ x: 3, y: 2, length: 2, orientation: "Vertical" }]
+// a minute per move, so a player who won't reveal their fleet can lose on time
+const time_control = { PerMove: { seconds: 60 } }
+
+const commitFleet = async (agent, game_address, fleet, salt, timestamp) => {
+  const commitment = await agent.callSync("main", "hash_secret", { key: "fleet", data: fleet, salt })
+  return makeMove(agent, { game: game_address, timestamp, move_type: { Commit: { key: "fleet", commitment: commitment.Ok } } })
+}
+
+const revealFleet = (agent, game_address, fleet, salt, timestamp) =>
+  makeMove(agent, { game: game_address, timestamp, move_type: { Reveal: { key: "fleet", data: fleet, salt } } })
+
+module.exports = (scenario) => {
+	scenario("Can play battleship with hidden fleets", async (s, t, { alice, bob }) => {
+
+	  const untimed = await alice.callSync("main", "create_game", { opponents: [bob.agentId], timestamp: 0, game_type: "Battleship", settings, time_control: null })
+	  t.equal(untimed.Ok, undefined, "Battleship can't be played without a time control")
+
+	  let game_address = await createGame(alice, bob, "Battleship", settings, time_control);
+
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: { Fire: { x: 0, y: 0 } } })
+	  t.equal(lastResult().Ok, undefined, "Cannot fire before the fleets are committed")
+
+	  await commitFleet(bob, game_address, bob_fleet, "bob's salt", 0)
+	  t.notEqual(lastResult().Ok, undefined, "Bob committed to his fleet")
+	  await commitFleet(alice, game_address, alice_fleet, "alice's salt", 1)
+	  t.notEqual(lastResult().Ok, undefined, "Alice committed to her fleet")
+
+	  const moves = [
+	    [bob, { Fire: { x: 0, y: 0 } }],
+	    [alice, { Report: { result: "Hit" } }],
+	    [alice, { Fire: { x: 3, y: 3 } }],
+	    [bob, { Report: { result: "Hit" } }],
+	    [bob, { Fire: { x: 1, y: 0 } }],
+	    [alice, { Report: { result: "Sunk" } }],
+	  ]
+	  for (const [i, [agent, move_type]] of moves.entries()) {
+	    await makeMove(agent, { game: game_address, timestamp: i + 2, move_type })
+	    t.notEqual(lastResult().Ok, undefined, `Move ${i} was accepted`)
+	  }
+
+	  let result = await alice.callSync("main", "get_result", { game_address })
+	  t.equal(result.Ok, "InProgress", "The game is not over until the fleets are revealed")
+
+	  await makeMove(alice, { game: game_address, timestamp: 8, move_type: { Fire: { x: 3, y: 2 } } })
+	  t.equal(lastResult().Ok, undefined, "Only reveals can be made once all ships are sunk")
+
+	  await revealFleet(alice, game_address, bob_fleet, "alice's salt", 9)
+	  t.equal(lastResult().Ok, undefined, "A fleet that doesn't match the commitment is rejected")
+
+	  await revealFleet(alice, game_address, alice_fleet, "alice's salt", 9)
+	  await revealFleet(bob, game_address, bob_fleet, "bob's salt", 10)
+	  t.notEqual(lastResult().Ok, undefined, "Both fleets were revealed")
+
+	  await renderState(alice, game_address)
+
+	  result = await alice.callSync("main", "get_result", { game_address })
+	  t.deepEqual(result.Ok, { Winner: bob.agentId }, "Bob sank Alice's fleet")
+	})
+
+	scenario("A player who reports falsely loses", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "Battleship", settings, time_control);
+
+	  await commitFleet(bob, game_address, bob_fleet, "bob's salt", 0)
+	  await commitFleet(alice, game_address, alice_fleet, "alice's salt", 1)
+
+	  const moves = [
+	    [bob, { Fire: { x: 0, y: 0 } }],
+	    [alice, { Report: { result: "Miss" } }], // a lie
+	    [alice, { Fire: { x: 3, y: 2 } }],
+	    [bob, { Report: { result: "Hit" } }],
+	    [bob, { Fire: { x: 1, y: 1 } }],
+	    [alice, { Report: { result: "Miss" } }],
+	    [alice, { Fire: { x: 3, y: 3 } }],
+	    [bob, { Report: { result: "Sunk" } }],
+	  ]
+	  for (const [i, [agent, move_type]] of moves.entries()) {
+	    await makeMove(agent, { game: game_address, timestamp: i + 2, move_type })
+	  }
+
+	  await revealFleet(alice, game_address, alice_fleet, "alice's salt", 10)
+	  await revealFleet(bob, game_address, bob_fleet, "bob's salt", 11)
+
+	  const result = await alice.callSync("main", "get_result", { game_address })
+	  t.deepEqual(result.Ok, { Winner: bob.agentId }, "Alice's false report was caught when her fleet was revealed")
+	})
+
+	scenario("A player who won't reveal their fleet loses on time", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "Battleship", settings, time_control);
+
+	  await commitFleet(bob, game_address, bob_fleet, "bob's salt", 0)
+	  await commitFleet(alice, game_address, alice_fleet, "alice's salt", 1)
+
+	  const moves = [
+	    [bob, { Fire: { x: 0, y: 0 } }],
+	    [alice, { Report: { result: "Hit" } }],
+	    [alice, { Fire: { x: 3, y: 3 } }],
+	    [bob, { Report: { result: "Hit" } }],
+	    [bob, { Fire: { x: 1, y: 0 } }],
+	    [alice, { Report: { result: "Sunk" } }],
+	  ]
+	  for (const [i, [agent, move_type]] of moves.entries()) {
+	    await makeMove(agent, { game: game_address, timestamp: i + 2, move_type })
+	  }
+
+	  // Alice lost and never reveals her fleet
+	  await revealFleet(bob, game_address, bob_fleet, "bob's salt", 8)
+
+	  await makeMove(bob, { game: game_address, timestamp: 30, move_type: "ClaimTimeout" })
+	  t.equal(lastResult().Ok, undefined, "Alice still has time to reveal her fleet")
+
+	  await makeMove(bob, { game: game_address, timestamp: 100, move_type: "ClaimTimeout" })
+	  t.notEqual(lastResult().Ok, undefined, "Bob claims the win once Alice's time is up")
+
+	  const result = await alice.callSync("main", "get_result", { game_address })
+	  t.deepEqual(result.Ok, { Winner: bob.agentId }, "The game ended without Alice's fleet")
+	})
+}
@@ -7,7 +7,7 @@ module.exports = (scenario) => {
       console.log(whoamiResult)
       t.equal(whoamiResult.Ok.length, 63)
 
-	  let game_address = await createGame(alice, bob);
+	  let game_address = await createGame(alice, bob, "Checkers");
 
 	  // agent 2 must go first
 	  await makeMove(bob, {
@@ -50,4 +50,21 @@ module.exports = (scenario) => {
 	  })
 
 	})
-} 
+
+	scenario("Captures are compulsory", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "Checkers");
+
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: {MovePiece: { from: {x: 1, y: 5}, to: {x: 2, y: 4} }} })
+	  await makeMove(alice, { game: game_address, timestamp: 1, move_type: {MovePiece: { from: {x: 4, y: 2}, to: {x: 3, y: 3} }} })
+
+	  await makeMove(bob, { game: game_address, timestamp: 2, move_type: {MovePiece: { from: {x: 3, y: 5}, to: {x: 4, y: 4} }} })
+	  t.equal(lastResult().Ok, undefined, "Bob cannot ignore the capture")
+
+	  await makeMove(bob, { game: game_address, timestamp: 2, move_type: {MovePiece: { from: {x: 2, y: 4}, to: {x: 4, y: 2} }} })
+	  t.notEqual(lastResult().Ok, undefined, "Bob jumped over Alices piece")
+
+	  let state = await getState(alice, game_address)
+	  t.equal(state.Ok.game_state.player_1.pieces.length, 11, "Alice lost a piece")
+	})
+}
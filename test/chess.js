@@ -0,0 +1,94 @@
+const {results, lastResult, makeMove, createGame, renderState, getState} = require('./helpers')
+
+const movePiece = (from, to, promotion=null) => ({MovePiece: { from, to, promotion }})
+
+module.exports = (scenario) => {
+	scenario("Can play a game of chess to checkmate and export it as PGN", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "Chess");
+
+	  // bob accepted the game so plays white and moves first
+	  const moves = [
+	    [bob, movePiece({x: 4, y: 1}, {x: 4, y: 3})],   // e4
+	    [alice, movePiece({x: 4, y: 6}, {x: 4, y: 4})], // e5
+	    [bob, movePiece({x: 5, y: 0}, {x: 2, y: 3})],   // Bc4
+	    [alice, movePiece({x: 1, y: 7}, {x: 2, y: 5})], // Nc6
+	    [bob, movePiece({x: 3, y: 0}, {x: 7, y: 4})],   // Qh5
+	    [alice, movePiece({x: 6, y: 7}, {x: 5, y: 5})], // Nf6
+	    [bob, movePiece({x: 7, y: 4}, {x: 5, y: 6})],   // Qxf7#
+	  ]
+	  for (const [i, [agent, move_type]] of moves.entries()) {
+	    await makeMove(agent, { game: game_address, timestamp: i, move_type })
+	    t.notEqual(lastResult().Ok, undefined, `Move ${i} was accepted`)
+	  }
+
+	  await renderState(alice, game_address)
+
+	  const result = await alice.callSync("main", "get_result", { game_address })
+	  t.deepEqual(result.Ok, { Winner: bob.agentId }, "White won by checkmate")
+
+	  const pgn = await alice.callSync("main", "get_pgn", { game_address })
+	  console.log(pgn.Ok)
+	  t.ok(pgn.Ok.includes("1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0"), "The PGN has the moves in algebraic notation")
+
+	  // both agents should see the same game state
+	  t.deepEqual(await getState(bob, game_address), await getState(alice, game_address), "Alice and Bob both see the same game state")
+	})
+
+	scenario("Illegal chess moves are rejected", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "Chess");
+
+	  await makeMove(alice, { game: game_address, timestamp: 0, move_type: movePiece({x: 4, y: 6}, {x: 4, y: 4}) })
+	  t.equal(lastResult().Ok, undefined, "Black cannot move first")
+
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: movePiece({x: 4, y: 1}, {x: 4, y: 4}) })
+	  t.equal(lastResult().Ok, undefined, "A pawn cannot move three squares")
+
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: movePiece({x: 0, y: 0}, {x: 0, y: 2}) })
+	  t.equal(lastResult().Ok, undefined, "A rook cannot jump over a pawn")
+	})
+
+	scenario("Can start a chess game from a FEN or a PGN", async (s, t, { alice, bob }) => {
+
+	  // white to move and mate in one with Ra8#
+	  const fen_game = await alice.callSync("main", "create_game_from_fen", { opponent: bob.agentId, timestamp: 0, fen: "7k/8/6K1/8/8/8/8/R7 w - - 0 1" })
+	  t.notEqual(fen_game.Ok, undefined, "Game created from a FEN")
+
+	  await makeMove(bob, { game: fen_game.Ok, timestamp: 1, move_type: movePiece({x: 0, y: 0}, {x: 0, y: 7}) })
+	  const result = await alice.callSync("main", "get_result", { game_address: fen_game.Ok })
+	  t.deepEqual(result.Ok, { Winner: bob.agentId }, "White won by checkmate")
+
+	  const invalid_fen = await alice.callSync("main", "create_game_from_fen", { opponent: bob.agentId, timestamp: 1, fen: "not a fen" })
+	  t.equal(invalid_fen.Ok, undefined, "An invalid FEN is rejected")
+
+	  // black's knight stands where the pawn taken en passant would be
+	  const own_piece = await alice.callSync("main", "create_game_from_fen", { opponent: bob.agentId, timestamp: 1, fen: "rnbqkbnr/pppp1ppp/8/8/3np3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 2" })
+	  t.equal(own_piece.Ok, undefined, "A FEN with an en passant square that no pawn passed over is rejected")
+
+	  const pgn = '[Event "Casual game"]\n\n1. e4 {a classic} e5 2. Nf3 Nc6 3. Bb5 *'
+	  const pgn_game = await alice.callSync("main", "create_game_from_pgn", { opponent: bob.agentId, timestamp: 2, pgn })
+	  t.notEqual(pgn_game.Ok, undefined, "Game created from a PGN")
+
+	  // the game continues from the end of the PGN, so it is black's turn
+	  await makeMove(alice, { game: pgn_game.Ok, timestamp: 3, move_type: movePiece({x: 0, y: 6}, {x: 0, y: 5}) })
+	  t.notEqual(lastResult().Ok, undefined, "Black continued the game")
+
+	  const exported = await alice.callSync("main", "get_pgn", { game_address: pgn_game.Ok })
+	  t.ok(exported.Ok.includes("1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 *"), "The exported PGN includes the imported moves")
+
+	  const invalid_pgn = await alice.callSync("main", "create_game_from_pgn", { opponent: bob.agentId, timestamp: 4, pgn: "1. e4 e4" })
+	  t.equal(invalid_pgn.Ok, undefined, "A PGN with an illegal move is rejected")
+	})
+
+	scenario("Castling can be written with letters or zeros", async (s, t, { alice, bob }) => {
+	  for (const [queen_side, king_side] of [["O-O-O", "O-O"], ["0-0-0", "0-0"]]) {
+	    const pgn = `1. d4 d5 2. Nc3 Nc6 3. Bf4 Bf5 4. Qd2 Qd7 5. ${queen_side} e6 6. e3 Nf6 7. Nf3 Be7 8. Be2 ${king_side} *`
+	    const game = await alice.callSync("main", "create_game_from_pgn", { opponent: bob.agentId, timestamp: 0, pgn })
+	    t.notEqual(game.Ok, undefined, `Game created from a PGN castling with ${queen_side} and ${king_side}`)
+
+	    const exported = await alice.callSync("main", "get_pgn", { game_address: game.Ok })
+	    t.ok(exported.Ok.includes("5. O-O-O e6 6. e3 Nf6 7. Nf3 Be7 8. Be2 O-O *"), "White castled queen side and black king side")
+	  }
+	})
+}
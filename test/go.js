@@ -0,0 +1,53 @@
+const {results, lastResult, makeMove, createGame, renderState, getState} = require('./helpers')
+
+const place = (x, y) => ({Place: { pos: {x, y} }})
+
+module.exports = (scenario) => {
+	scenario("Can play a game of go to the end and export it as SGF", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "Go", { board_size: 9, komi: 0.5 });
+
+	  // bob accepted the game so plays black and moves first. Each player builds a wall down the board.
+	  for (let y = 0; y < 9; y++) {
+	    await makeMove(bob, { game: game_address, timestamp: 2 * y, move_type: place(4, y) })
+	    t.notEqual(lastResult().Ok, undefined, "Black placed a stone")
+	    await makeMove(alice, { game: game_address, timestamp: 2 * y + 1, move_type: place(5, y) })
+	    t.notEqual(lastResult().Ok, undefined, "White placed a stone")
+	  }
+
+	  await makeMove(bob, { game: game_address, timestamp: 18, move_type: place(4, 0) })
+	  t.equal(lastResult().Ok, undefined, "Cannot place a stone on another stone")
+
+	  await makeMove(bob, { game: game_address, timestamp: 18, move_type: "Pass" })
+	  await makeMove(alice, { game: game_address, timestamp: 19, move_type: "Pass" })
+	  t.notEqual(lastResult().Ok, undefined, "Both players passed")
+
+	  await makeMove(bob, { game: game_address, timestamp: 20, move_type: place(0, 0) })
+	  t.equal(lastResult().Ok, undefined, "No stones can be placed after both players pass")
+
+	  await makeMove(bob, { game: game_address, timestamp: 20, move_type: {MarkDead: { stones: [] }} })
+	  await makeMove(bob, { game: game_address, timestamp: 21, move_type: "AcceptScore" })
+	  t.equal(lastResult().Ok, undefined, "Cannot accept your own dead stones")
+
+	  await makeMove(alice, { game: game_address, timestamp: 21, move_type: "AcceptScore" })
+	  t.notEqual(lastResult().Ok, undefined, "White accepted the score")
+
+	  await renderState(alice, game_address)
+
+	  const result = await alice.callSync("main", "get_result", { game_address })
+	  t.deepEqual(result.Ok, { Winner: bob.agentId }, "Black won 45 to 36.5")
+
+	  const sgf = await alice.callSync("main", "get_sgf", { game_address })
+	  console.log(sgf.Ok)
+	  t.ok(sgf.Ok.includes("RE[B+8.5]"), "The SGF has the result")
+	  t.ok(sgf.Ok.includes(";B[ea]"), "The SGF has the moves")
+
+	  // both agents should see the same game state
+	  t.deepEqual(await getState(bob, game_address), await getState(alice, game_address), "Alice and Bob both see the same game state")
+	})
+
+	scenario("Go games must use a standard board size", async (s, t, { alice, bob }) => {
+	  const game_address = await createGame(alice, bob, "Go", { board_size: 10 })
+	  t.equal(game_address, undefined, "A 10x10 board is rejected")
+	})
+}
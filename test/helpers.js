@@ -9,13 +9,17 @@ let results = []
 module.exports = {
   results: results,
   lastResult: (back=0) => results[results.length-1-back],
+  // the current unix time in seconds, as the zome functions take it
+  now: () => Math.floor(Date.now() / 1000),
   makeMove: async (agent, game_move) => {
     const result = await agent.callSync("main", "make_move", { new_move: game_move })
     results.push(result)
     return result
   },
-  createGame: async (agent, opponent) => {
-    const result = await agent.callSync("main", "create_game", { opponent: opponent.agentId, timestamp: 0 })
+  createGame: async (agent, opponents, game_type="TicTacToe", settings=null, time_control=null) => {
+    // a single opponent or a list of them, in turn order
+    opponents = [].concat(opponents).map(opponent => opponent.agentId)
+    const result = await agent.callSync("main", "create_game", { opponents, timestamp: 0, game_type, settings, time_control })
     results.push(result)
     return result.Ok
   },
@@ -15,6 +15,7 @@ const diorama = new Diorama({
   instances: {
     alice: dna,
     bob: dna,
+    carol: dna,
   },
   bridges: [],
   debugLog: false,
@@ -22,12 +23,16 @@ const diorama = new Diorama({
   middleware: backwardCompatibilityMiddleware,
 })
 
-// uncomment one of these
-// require('./tictactoe')(diorama.registerScenario)
+require('./tictactoe')(diorama.registerScenario)
 require('./checkers')(diorama.registerScenario)
-
-// test the matchmaking 
-// require('./matchmaking')(diorama.registerScenario)
+require('./chess')(diorama.registerScenario)
+require('./go')(diorama.registerScenario)
+require('./battleship')(diorama.registerScenario)
+require('./rockpaperscissors')(diorama.registerScenario)
+require('./pig')(diorama.registerScenario)
+
+// test the matchmaking
+require('./matchmaking')(diorama.registerScenario)
 
 
 diorama.run()
@@ -1,10 +1,11 @@
+const {lastResult, makeMove, now} = require('./helpers')
 
 module.exports = (scenario)=> {
   scenario("Bob can accept Alices proposal, create a game and Alice can see the game", async (s, t, { alice, bob }) => {
     const addr = await alice.callSync("main", "create_proposal", {message : "sup"})
     t.equal(addr.Ok.length, 46, "Proposal was created successfully")
 
-    const proposals = await bob.callSync("main", "get_proposals", {})
+    const proposals = await bob.callSync("main", "get_proposals", { now: now() })
     console.log(proposals)
     t.equal(proposals.Ok.length, 1, "Bob could retrieve Alices Proposal")
 
@@ -16,13 +17,166 @@ module.exports = (scenario)=> {
       games.Ok, 
       [{ 
         entry: { 
-          player_1: bob.agentId,
-          player_2: alice.agentId,
+          game_type: "TicTacToe",
+          settings: null,
+          players: [bob.agentId, alice.agentId],
+          teams: [],
+          time_control: null,
+          rated: false,
+          proposal: proposals.Ok[0].address,
           created_at: 0
         }, 
-        address: games.Ok[0].address
+        address: games.Ok[0].address,
+        status: "Pending"
       }],
       "The game was created as expected"
     )
   })
+
+  scenario("A proposal can be accepted on behalf of more players", async (s, t, { alice, bob, carol }) => {
+    const addr = await alice.callSync("main", "create_proposal", {message : "anyone for a game?"})
+
+    const acceptance = await bob.callSync("main", "accept_proposal", { proposal_addr: addr.Ok, created_at: 0, other_players: [carol.agentId] })
+    t.equal(acceptance.Ok, undefined, "Tic-tac-toe can't be started with three players")
+  })
+
+  scenario("The proposer confirms one response and the others are rejected", async (s, t, { alice, bob, carol }) => {
+    const addr = await alice.callSync("main", "create_proposal", {message : "one game only"})
+
+    const from_bob = await bob.callSync("main", "accept_proposal", { proposal_addr: addr.Ok, created_at: 0 })
+    const from_carol = await carol.callSync("main", "accept_proposal", { proposal_addr: addr.Ok, created_at: 0 })
+    t.notEqual(from_carol.Ok, undefined, "More than one agent can respond to a proposal")
+
+    await makeMove(alice, { game: from_bob.Ok, timestamp: 0, move_type: { Place: { pos: { x: 0, y: 0 } } } })
+    t.equal(lastResult().Ok, undefined, "No moves can be made before the game is confirmed")
+    t.equal(JSON.parse(lastResult().Err.Internal).code, "NOT_CONFIRMED", "The game is waiting for Alice to confirm it")
+
+    const by_bob = await bob.callSync("main", "confirm_response", { proposal_addr: addr.Ok, game_addr: from_bob.Ok })
+    t.equal(by_bob.Ok, undefined, "Only Alice can confirm a response to her proposal")
+
+    const confirmed = await alice.callSync("main", "confirm_response", { proposal_addr: addr.Ok, game_addr: from_bob.Ok })
+    t.equal(confirmed.Ok, from_bob.Ok, "Alice confirmed the game with Bob")
+
+    const again = await alice.callSync("main", "confirm_response", { proposal_addr: addr.Ok, game_addr: from_carol.Ok })
+    t.equal(again.Ok, undefined, "Only one response can be confirmed")
+
+    const responses = await alice.callSync("main", "check_responses", { proposal_addr: addr.Ok })
+    t.deepEqual(
+      responses.Ok.map(response => [response.address, response.status]).sort(),
+      [[from_bob.Ok, "Confirmed"], [from_carol.Ok, "Rejected"]].sort(),
+      "Bob's game was confirmed and Carol's rejected"
+    )
+
+    await makeMove(alice, { game: from_bob.Ok, timestamp: 0, move_type: { Place: { pos: { x: 0, y: 0 } } } })
+    t.notEqual(lastResult().Ok, undefined, "Alice can move in the confirmed game")
+
+    await makeMove(alice, { game: from_carol.Ok, timestamp: 0, move_type: { Place: { pos: { x: 0, y: 0 } } } })
+    t.equal(lastResult().Ok, undefined, "The rejected game can't be played")
+
+    const proposals = await carol.callSync("main", "get_proposals", { now: now() })
+    t.equal(proposals.Ok.length, 0, "The proposal is no longer listed")
+
+    const late = await carol.callSync("main", "accept_proposal", { proposal_addr: addr.Ok, created_at: 0 })
+    t.equal(late.Ok, undefined, "A closed proposal can't be accepted")
+  })
+
+  scenario("Only a game the proposer confirmed can be started, by the proposer", async (s, t, { alice, bob, carol }) => {
+    const addr = await alice.callSync("main", "create_proposal", {message : "first come"})
+    const from_bob = await bob.callSync("main", "accept_proposal", { proposal_addr: addr.Ok, created_at: 0 })
+    const from_carol = await carol.callSync("main", "accept_proposal", { proposal_addr: addr.Ok, created_at: 0 })
+
+    await makeMove(bob, { game: from_bob.Ok, timestamp: 0, move_type: "Confirm" })
+    t.equal(lastResult().Ok, undefined, "Bob can't confirm the game himself")
+
+    await alice.callSync("main", "confirm_response", { proposal_addr: addr.Ok, game_addr: from_bob.Ok })
+    const moves = await bob.callSync("main", "get_moves", { game_address: from_bob.Ok })
+    t.deepEqual(moves.Ok.map(game_move => [game_move.author, game_move.move_type]), [[alice.agentId, "Confirm"]], "The game starts with Alice's confirmation")
+
+    await makeMove(alice, { game: from_carol.Ok, timestamp: 0, move_type: "Confirm" })
+    t.equal(lastResult().Ok, undefined, "Alice can't start the game she didn't confirm")
+
+    await makeMove(carol, { game: from_carol.Ok, timestamp: 0, move_type: "Confirm" })
+    t.equal(lastResult().Ok, undefined, "Carol can't start her rejected game either")
+  })
+
+  scenario("Only the author of a proposal can remove it", async (s, t, { alice, bob }) => {
+    const addr = await alice.callSync("main", "create_proposal", {message : "changed my mind"})
+
+    const by_bob = await bob.callSync("main", "remove_proposal", { proposal_addr: addr.Ok })
+    t.equal(by_bob.Ok, undefined, "Bob can't remove Alice's proposal")
+
+    const by_alice = await alice.callSync("main", "remove_proposal", { proposal_addr: addr.Ok })
+    t.notEqual(by_alice.Ok, undefined, "Alice can remove her own proposal")
+
+    const proposals = await bob.callSync("main", "get_proposals", { now: now() })
+    t.equal(proposals.Ok.length, 0, "The removed proposal is no longer listed")
+  })
+
+  scenario("The game is created on the terms of the proposal and proposals can be filtered by them", async (s, t, { alice, bob }) => {
+    const time_control = { Total: { seconds: 300, increment: 5 } }
+    const rated = await alice.callSync("main", "create_proposal", { message: "rated go", game_type: "Go", settings: { board_size: 9 }, time_control, rated: true, first_mover: "Acceptor" })
+    t.notEqual(rated.Ok, undefined, "Alice proposed a rated game of go")
+    await alice.callSync("main", "create_proposal", { message: "casual" })
+
+    const go = await bob.callSync("main", "get_proposals", { filter: { game_type: "Go" }, now: now() })
+    t.deepEqual(go.Ok.map(proposal => proposal.address), [rated.Ok], "Only the go proposal is listed")
+    t.deepEqual(
+      [go.Ok[0].entry.settings, go.Ok[0].entry.time_control, go.Ok[0].entry.rated, go.Ok[0].entry.first_mover],
+      [{ board_size: 9 }, time_control, true, "Acceptor"],
+      "The proposal holds its terms"
+    )
+
+    const unrated = await bob.callSync("main", "get_proposals", { filter: { rated: false }, now: now() })
+    t.equal(unrated.Ok.length, 1, "Only the casual proposal is unrated")
+    t.equal(unrated.Ok[0].entry.game_type, "TicTacToe", "Proposals are for tic-tac-toe unless said otherwise")
+
+    const acceptance = await bob.callSync("main", "accept_proposal", { proposal_addr: rated.Ok, created_at: 0 })
+    const game = await bob.callSync("main", "check_responses", { proposal_addr: rated.Ok })
+    t.equal(game.Ok[0].address, acceptance.Ok, "Bob responded with a game")
+    t.deepEqual(
+      [game.Ok[0].entry.game_type, game.Ok[0].entry.settings, game.Ok[0].entry.time_control, game.Ok[0].entry.rated],
+      ["Go", { board_size: 9 }, time_control, true],
+      "The game was created on the terms of the proposal"
+    )
+    t.deepEqual(game.Ok[0].entry.players, [alice.agentId, bob.agentId], "Bob moves first as Alice asked")
+  })
+
+  scenario("The rules of the game decide which seat moves first", async (s, t, { alice, bob }) => {
+    // black to move, and Alice wants to make the first move
+    const settings = { fen: "7k/8/6K1/8/8/8/8/R7 b - - 0 1" }
+    const addr = await alice.callSync("main", "create_proposal", { message: "black to move", game_type: "Chess", settings, first_mover: "Proposer" })
+
+    const acceptance = await bob.callSync("main", "accept_proposal", { proposal_addr: addr.Ok, created_at: 0 })
+    t.notEqual(acceptance.Ok, undefined, "Bob accepted the proposal")
+    const responses = await alice.callSync("main", "check_responses", { proposal_addr: addr.Ok })
+    t.deepEqual(responses.Ok[0].entry.players, [alice.agentId, bob.agentId], "Alice plays black, which moves first from this position")
+
+    await alice.callSync("main", "confirm_response", { proposal_addr: addr.Ok, game_addr: acceptance.Ok })
+    await makeMove(alice, { game: acceptance.Ok, timestamp: 0, move_type: { MovePiece: { from: { x: 7, y: 7 }, to: { x: 6, y: 7 }, promotion: null } } })
+    t.notEqual(lastResult().Ok, undefined, "Alice made the first move")
+  })
+
+  scenario("An expired proposal is no longer listed and can't be accepted", async (s, t, { alice, bob }) => {
+    const past = await alice.callSync("main", "create_proposal", { message: "yesterday", expires_at: now() - 3600 })
+    t.equal(past.Ok, undefined, "A proposal can't expire before it is made")
+
+    const expires_at = now() + 3600
+    const addr = await alice.callSync("main", "create_proposal", { message: "for an hour", expires_at })
+    t.notEqual(addr.Ok, undefined, "Alice proposed a game for the next hour")
+
+    const open = await bob.callSync("main", "get_proposals", { now: now() })
+    t.deepEqual(open.Ok.map(proposal => proposal.entry.expires_at), [expires_at], "The proposal is listed until it expires")
+
+    const later = await bob.callSync("main", "get_proposals", { now: expires_at })
+    t.equal(later.Ok.length, 0, "The proposal is hidden once it has expired")
+
+    const no_time = await bob.callSync("main", "get_proposals", {})
+    t.equal(no_time.Ok, undefined, "Proposals can't be listed without the time, so expired ones are always hidden")
+
+    const late = await bob.callSync("main", "accept_proposal", { proposal_addr: addr.Ok, created_at: expires_at })
+    t.equal(late.Ok, undefined, "An expired proposal can't be accepted")
+
+    const in_time = await bob.callSync("main", "accept_proposal", { proposal_addr: addr.Ok, created_at: now() })
+    t.notEqual(in_time.Ok, undefined, "The proposal can be accepted before it expires")
+  })
 }
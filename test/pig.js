@@ -0,0 +1,96 @@
+const {lastResult, makeMove, createGame, renderState, getState} = require('./helpers')
+
+const requestRoll = async (agent, game_address, key, seed, timestamp) => {
+  const commitment = await agent.callSync("main", "hash_secret", { key, data: seed, salt: `${seed} salt` })
+  return makeMove(agent, { game: game_address, timestamp, move_type: { RandomnessRequest: { key, commitment: commitment.Ok } } })
+}
+
+const revealSeed = (agent, game_address, key, seed, timestamp) =>
+  makeMove(agent, { game: game_address, timestamp, move_type: { RandomnessReveal: { key, seed, salt: `${seed} salt` } } })
+
+module.exports = (scenario) => {
+	scenario("Two players roll a shared die by committing to and revealing seeds", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "Pig", { target: 20 });
+
+	  await requestRoll(alice, game_address, "roll 1", "alice seed", 0)
+	  t.equal(lastResult().Ok, undefined, "Only the player to move can start a roll")
+
+	  await requestRoll(bob, game_address, "roll 1", "bob seed", 1)
+	  t.notEqual(lastResult().Ok, undefined, "Bob started a roll by committing to his seed")
+
+	  await revealSeed(bob, game_address, "roll 1", "bob seed", 2)
+	  t.equal(lastResult().Ok, undefined, "A seed can't be revealed before every player has committed to one")
+
+	  await requestRoll(alice, game_address, "roll 2", "alice seed", 3)
+	  t.equal(lastResult().Ok, undefined, "Alice has to answer the roll Bob started")
+
+	  await requestRoll(alice, game_address, "roll 1", "alice seed", 4)
+	  t.notEqual(lastResult().Ok, undefined, "Alice committed to her seed for the roll")
+
+	  await makeMove(bob, { game: game_address, timestamp: 5, move_type: "Hold" })
+	  t.equal(lastResult().Ok, undefined, "Bob can't hold while the die is being rolled")
+
+	  await revealSeed(alice, game_address, "roll 1", "not alice seed", 6)
+	  t.equal(lastResult().Ok, undefined, "A revealed seed must match the commitment")
+
+	  await revealSeed(alice, game_address, "roll 1", "alice seed", 7)
+	  await revealSeed(bob, game_address, "roll 1", "bob seed", 8)
+	  t.notEqual(lastResult().Ok, undefined, "Both seeds are revealed")
+
+	  const state = (await getState(alice, game_address)).Ok.game_state
+	  t.ok(state.last_roll >= 1 && state.last_roll <= 6, "The die was rolled from the seeds")
+	  t.equal(state.rolling, null, "The roll is over")
+	  if (state.last_roll === 1) {
+	    t.deepEqual([state.turn_total, state.to_move], [0, alice.agentId], "Rolling a 1 passes the turn to Alice")
+	  } else {
+	    t.deepEqual([state.turn_total, state.to_move], [state.last_roll, bob.agentId], "The roll is added to Bob's turn total")
+	    await makeMove(bob, { game: game_address, timestamp: 9, move_type: "Hold" })
+	    const held = (await getState(alice, game_address)).Ok.game_state
+	    t.deepEqual([held.scores, held.to_move], [[0, state.last_roll], alice.agentId], "Bob banked his roll and it is Alice's turn")
+	  }
+
+	  await renderState(alice, game_address)
+	})
+
+	scenario("Three players go round the table and a draw needs all of them to agree", async (s, t, { alice, bob, carol }) => {
+
+	  const timed = await alice.callSync("main", "create_game", { opponents: [bob.agentId, carol.agentId], timestamp: 0, game_type: "Pig", settings: null, time_control: { PerMove: { seconds: 60 } } })
+	  t.equal(timed.Ok, undefined, "A game for three players can't have a time control")
+
+	  let game_address = await createGame(alice, [bob, carol], "Pig", { target: 20 });
+	  t.notEqual(game_address, undefined, "Pig can be played by three players")
+
+	  await requestRoll(bob, game_address, "roll 1", "bob seed", 1)
+	  await requestRoll(carol, game_address, "roll 1", "carol seed", 2)
+	  await revealSeed(bob, game_address, "roll 1", "bob seed", 3)
+	  t.equal(lastResult().Ok, undefined, "Bob can't reveal before Alice has committed to a seed as well")
+
+	  await requestRoll(alice, game_address, "roll 1", "alice seed", 4)
+	  await revealSeed(alice, game_address, "roll 1", "alice seed", 5)
+	  await revealSeed(bob, game_address, "roll 1", "bob seed", 6)
+	  await revealSeed(carol, game_address, "roll 1", "carol seed", 7)
+	  t.notEqual(lastResult().Ok, undefined, "All three seeds are revealed")
+
+	  let state = (await getState(alice, game_address)).Ok.game_state
+	  if (state.last_roll === 1) {
+	    t.equal(state.to_move, carol.agentId, "Rolling a 1 passes the turn from Bob to Carol")
+	  } else {
+	    await makeMove(bob, { game: game_address, timestamp: 8, move_type: "Hold" })
+	    state = (await getState(alice, game_address)).Ok.game_state
+	    t.deepEqual([state.scores, state.to_move], [[0, state.scores[1], 0], carol.agentId], "Bob banked his roll and it is Carol's turn")
+	  }
+
+	  await makeMove(alice, { game: game_address, timestamp: 9, move_type: "OfferDraw" })
+	  await makeMove(bob, { game: game_address, timestamp: 10, move_type: "AcceptDraw" })
+	  let result = await alice.callSync("main", "get_result", { game_address })
+	  t.equal(result.Ok, "InProgress", "One player accepting isn't enough for a draw")
+
+	  await makeMove(bob, { game: game_address, timestamp: 11, move_type: "AcceptDraw" })
+	  t.equal(lastResult().Ok, undefined, "Bob can't accept the draw twice")
+
+	  await makeMove(carol, { game: game_address, timestamp: 12, move_type: "AcceptDraw" })
+	  result = await alice.callSync("main", "get_result", { game_address })
+	  t.equal(result.Ok, "Draw", "The game is a draw once every other player has accepted")
+	})
+}
@@ -0,0 +1,54 @@
+const {results, lastResult, makeMove, createGame, renderState, getState} = require('./helpers')
+
+const seal = async (agent, game_address, hand, salt, timestamp) => {
+  const commitment = await agent.callSync("main", "hash_move", { game_address, move_type: hand, salt })
+  return makeMove(agent, { game: game_address, timestamp, move_type: { SealMove: { commitment: commitment.Ok } } })
+}
+
+const reveal = (agent, game_address, hand, salt, timestamp) =>
+  makeMove(agent, { game: game_address, timestamp, move_type: { RevealMove: { move_type: hand, salt } } })
+
+module.exports = (scenario) => {
+	scenario("Can play rock-paper-scissors in simultaneous rounds", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "RockPaperScissors", { best_of: 3 });
+
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: "Rock" })
+	  t.equal(lastResult().Ok, undefined, "Hands can't be played in the open")
+
+	  const rounds = [
+	    ["Rock", "Scissors"],
+	    ["Paper", "Paper"],
+	    ["Scissors", "Paper"],
+	  ]
+	  let timestamp = 0
+	  for (const [i, [alice_hand, bob_hand]] of rounds.entries()) {
+	    await seal(bob, game_address, bob_hand, `bob ${i}`, timestamp++)
+	    t.notEqual(lastResult().Ok, undefined, `Bob sealed his hand for round ${i}`)
+
+	    await reveal(bob, game_address, bob_hand, `bob ${i}`, timestamp++)
+	    t.equal(lastResult().Ok, undefined, "Cannot reveal before every player has sealed a move")
+
+	    await seal(alice, game_address, alice_hand, `alice ${i}`, timestamp++)
+	    await reveal(alice, game_address, alice_hand, `alice ${i}`, timestamp++)
+	    t.notEqual(lastResult().Ok, undefined, `Alice revealed her hand for round ${i}`)
+	    await reveal(bob, game_address, bob_hand, `bob ${i}`, timestamp++)
+	    t.notEqual(lastResult().Ok, undefined, `Bob revealed his hand for round ${i}`)
+	  }
+
+	  await renderState(alice, game_address)
+
+	  const result = await alice.callSync("main", "get_result", { game_address })
+	  t.deepEqual(result.Ok, { Winner: alice.agentId }, "Alice won two rounds, the tie doesn't count")
+	})
+
+	scenario("A revealed move must match the sealed one", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "RockPaperScissors");
+
+	  await seal(bob, game_address, "Rock", "bob's salt", 0)
+	  await seal(alice, game_address, "Paper", "alice's salt", 1)
+	  await reveal(bob, game_address, "Scissors", "bob's salt", 2)
+	  t.equal(lastResult().Ok, undefined, "Bob cannot change his hand after seeing Alice seal hers")
+	})
+}
@@ -3,7 +3,7 @@ const {results, lastResult, makeMove, createGame, renderState, getState} = requi
 module.exports = (scenario) => {
 	scenario("Can create a new game of tictactoe and make a move", async (s, t, { alice, bob }) => {
 
-	  let game_address = await createGame(alice, bob);
+	  let game_address = await createGame(alice, bob, "TicTacToe");
 
 	  // agent 2 must go first
 	  await makeMove(bob, {
@@ -45,4 +45,336 @@ module.exports = (scenario) => {
 	  })
 
 	})
-} 
+
+	scenario("A player can resign and draws can be offered and declined", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "TicTacToe");
+
+	  await makeMove(bob, {
+	    game: game_address,
+	    timestamp: 0,
+	    move_type: {Place: { pos: { x: 0, y: 0 } } },
+	  })
+
+	  await makeMove(alice, { game: game_address, timestamp: 1, move_type: "AcceptDraw" })
+	  t.equal(lastResult().Ok, undefined, "Alice cannot accept a draw that was never offered")
+
+	  await makeMove(alice, { game: game_address, timestamp: 1, move_type: "OfferDraw" })
+	  t.notEqual(lastResult().Ok, undefined, "Alice offered a draw")
+
+	  await makeMove(bob, { game: game_address, timestamp: 2, move_type: "DeclineDraw" })
+	  t.notEqual(lastResult().Ok, undefined, "Bob declined the draw")
+
+	  await makeMove(bob, { game: game_address, timestamp: 3, move_type: "Resign" })
+	  t.notEqual(lastResult().Ok, undefined, "Bob resigned")
+
+	  let state = await getState(alice, game_address)
+	  t.deepEqual(state.Ok.outcome, { Winner: alice.agentId }, "Alice won by resignation")
+
+	  await makeMove(alice, { game: game_address, timestamp: 4, move_type: "Resign" })
+	  t.equal(lastResult().Ok, undefined, "Alice cannot resign a finished game")
+	})
+
+	scenario("No moves can be made once the game has been won", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "TicTacToe");
+
+	  const moves = [
+	    [bob, { x: 0, y: 0 }],
+	    [alice, { x: 1, y: 1 }],
+	    [bob, { x: 1, y: 0 }],
+	    [alice, { x: 2, y: 2 }],
+	    [bob, { x: 2, y: 0 }],
+	  ]
+	  for (const [i, [agent, pos]] of moves.entries()) {
+	    await makeMove(agent, { game: game_address, timestamp: i, move_type: { Place: { pos } } })
+	    t.notEqual(lastResult().Ok, undefined, `Move ${i} was accepted`)
+	  }
+
+	  let state = await getState(alice, game_address)
+	  t.deepEqual(state.Ok.outcome, { Winner: bob.agentId }, "Bob completed the top row and won")
+
+	  await makeMove(alice, { game: game_address, timestamp: 5, move_type: { Place: { pos: { x: 0, y: 2 } } } })
+	  t.equal(lastResult().Ok, undefined, "Alice cannot place a piece after the game is over")
+	})
+
+	scenario("Filling the board without a line is a draw", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "TicTacToe");
+
+	  // X O X
+	  // X O O
+	  // O X X
+	  const moves = [
+	    [bob, { x: 0, y: 0 }],
+	    [alice, { x: 1, y: 1 }],
+	    [bob, { x: 2, y: 0 }],
+	    [alice, { x: 1, y: 0 }],
+	    [bob, { x: 1, y: 2 }],
+	    [alice, { x: 0, y: 2 }],
+	    [bob, { x: 0, y: 1 }],
+	    [alice, { x: 2, y: 1 }],
+	    [bob, { x: 2, y: 2 }],
+	  ]
+	  for (const [i, [agent, pos]] of moves.entries()) {
+	    await makeMove(agent, { game: game_address, timestamp: i, move_type: { Place: { pos } } })
+	    t.notEqual(lastResult().Ok, undefined, `Move ${i} was accepted`)
+	  }
+
+	  const result = await alice.callSync("main", "get_result", { game_address })
+	  t.equal(result.Ok, "Draw", "The game is reported as a draw")
+	})
+
+	scenario("Diagonal lines win, including on larger boards", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "TicTacToe");
+	  const moves = [
+	    [bob, { x: 0, y: 0 }],
+	    [alice, { x: 1, y: 0 }],
+	    [bob, { x: 1, y: 1 }],
+	    [alice, { x: 2, y: 0 }],
+	    [bob, { x: 2, y: 2 }],
+	  ]
+	  for (const [i, [agent, pos]] of moves.entries()) {
+	    await makeMove(agent, { game: game_address, timestamp: i, move_type: { Place: { pos } } })
+	  }
+	  let result = await alice.callSync("main", "get_result", { game_address })
+	  t.deepEqual(result.Ok, { Winner: bob.agentId }, "Bob won along the diagonal")
+
+	  // 5x5 board needing 4 in a row, the anti-diagonal is completed through its centre
+	  let big_game_address = await createGame(alice, bob, "TicTacToe", { board_size: 5, win_length: 4 })
+	  const big_moves = [
+	    [bob, { x: 4, y: 0 }],
+	    [alice, { x: 0, y: 0 }],
+	    [bob, { x: 3, y: 1 }],
+	    [alice, { x: 0, y: 1 }],
+	    [bob, { x: 1, y: 3 }],
+	    [alice, { x: 0, y: 2 }],
+	  ]
+	  for (const [i, [agent, pos]] of big_moves.entries()) {
+	    await makeMove(agent, { game: big_game_address, timestamp: i, move_type: { Place: { pos } } })
+	    t.notEqual(lastResult().Ok, undefined, `Move ${i} was accepted`)
+	  }
+	  result = await alice.callSync("main", "get_result", { game_address: big_game_address })
+	  t.equal(result.Ok, "InProgress", "Three in a row is not enough on this board")
+
+	  await makeMove(bob, { game: big_game_address, timestamp: 6, move_type: { Place: { pos: { x: 2, y: 2 } } } })
+	  result = await alice.callSync("main", "get_result", { game_address: big_game_address })
+	  t.deepEqual(result.Ok, { Winner: bob.agentId }, "Bob won with four along the anti-diagonal")
+
+	  const invalid = await alice.callSync("main", "create_game", { opponents: [bob.agentId], timestamp: 0, game_type: "TicTacToe", settings: { board_size: 3, win_length: 4 } })
+	  t.equal(invalid.Ok, undefined, "A win length longer than the board is rejected")
+	})
+
+	scenario("Games without hidden information reject commitments", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob);
+
+	  const commitment = await bob.callSync("main", "hash_secret", { key: "secret", data: { x: 1 }, salt: "pepper" })
+	  t.notEqual(commitment.Ok, undefined, "The hash of a secret can be computed without publishing it")
+
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: { Commit: { key: "secret", commitment: commitment.Ok } } })
+	  t.equal(lastResult().Ok, undefined, "Tic-tac-toe does not accept commitments")
+	})
+
+	scenario("Games without dice reject requests for randomness", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob);
+
+	  const commitment = await bob.callSync("main", "hash_secret", { key: "roll", data: "a random seed", salt: "pepper" })
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: { RandomnessRequest: { key: "roll", commitment: commitment.Ok } } })
+	  t.equal(lastResult().Ok, undefined, "Tic-tac-toe does not use randomness")
+	})
+
+	scenario("Tic-tac-toe can't be played by more than two players", async (s, t, { alice, bob, carol }) => {
+
+	  const game_address = await createGame(alice, [bob, carol]);
+	  t.equal(game_address, undefined, "A game of tic-tac-toe with three players is rejected")
+	})
+
+	scenario("Teams share their turns and results", async (s, t, { alice, bob, carol }) => {
+
+	  const teams = [
+	    { name: "crosses", members: [alice.agentId, carol.agentId], turns: "Rotation" },
+	    { name: "noughts", members: [bob.agentId], turns: "AnyMember" },
+	  ]
+	  const created = await alice.callSync("main", "create_team_game", { teams, timestamp: 0, game_type: "TicTacToe", settings: null })
+	  const game_address = created.Ok
+	  t.notEqual(game_address, undefined, "A team game was created")
+
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: { Place: { pos: { x: 0, y: 0 } } } })
+	  await makeMove(carol, { game: game_address, timestamp: 1, move_type: { Place: { pos: { x: 1, y: 0 } } } })
+	  t.equal(lastResult().Ok, undefined, "Carol must wait for Alice to take the team's first move")
+
+	  const moves = [
+	    [alice, { x: 1, y: 0 }],
+	    [bob, { x: 0, y: 1 }],
+	    [carol, { x: 1, y: 1 }],
+	    [bob, { x: 2, y: 2 }],
+	    [alice, { x: 1, y: 2 }],
+	  ]
+	  for (const [i, [agent, pos]] of moves.entries()) {
+	    await makeMove(agent, { game: game_address, timestamp: i + 2, move_type: { Place: { pos } } })
+	    t.notEqual(lastResult().Ok, undefined, `Move ${i} was accepted`)
+	  }
+
+	  const results = await carol.callSync("main", "get_team_results", { game_address })
+	  t.deepEqual(results.Ok, [
+	    { team: "crosses", members: [alice.agentId, carol.agentId], result: "Won" },
+	    { team: "noughts", members: [bob.agentId], result: "Lost" },
+	  ], "The crosses won as a team")
+	})
+
+	scenario("A player who runs out of time loses when their opponent claims it", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "TicTacToe", null, { PerMove: { seconds: 10 } });
+
+	  await makeMove(bob, { game: game_address, timestamp: 100, move_type: { Place: { pos: { x: 0, y: 0 } } } })
+	  await makeMove(alice, { game: game_address, timestamp: 105, move_type: { Place: { pos: { x: 1, y: 1 } } } })
+
+	  await makeMove(bob, { game: game_address, timestamp: 104, move_type: { Place: { pos: { x: 2, y: 2 } } } })
+	  t.equal(lastResult().Ok, undefined, "A move can't be timestamped before the move it follows")
+
+	  await makeMove(bob, { game: game_address, timestamp: 4000000000, move_type: { Place: { pos: { x: 2, y: 2 } } } })
+	  t.equal(lastResult().Ok, undefined, "A move can't be timestamped in the future")
+
+	  await makeMove(alice, { game: game_address, timestamp: 110, move_type: "ClaimTimeout" })
+	  t.equal(lastResult().Ok, undefined, "Bob still has time left")
+
+	  await makeMove(alice, { game: game_address, timestamp: 120, move_type: "ClaimTimeout" })
+	  t.notEqual(lastResult().Ok, undefined, "Alice claims the win once Bob's time is up")
+
+	  const result = await alice.callSync("main", "get_result", { game_address })
+	  t.deepEqual(result.Ok, { Winner: alice.agentId }, "Alice won on time")
+	})
+
+	scenario("Only the move that ends a player's turn gives them their time back", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "TicTacToe", null, { PerMove: { seconds: 10 } });
+
+	  await makeMove(bob, { game: game_address, timestamp: 100, move_type: { Place: { pos: { x: 0, y: 0 } } } })
+	  await makeMove(alice, { game: game_address, timestamp: 106, move_type: "OfferDraw" })
+	  await makeMove(bob, { game: game_address, timestamp: 109, move_type: "DeclineDraw" })
+
+	  let state = (await getState(alice, game_address)).Ok
+	  t.deepEqual(state.clocks.map(clock => clock.remaining), [1, 10], "Alice's clock kept running through the draw offer, Bob wasn't charged for answering it")
+	  t.equal(state.last_move_at, 109, "The clocks were moved on to the last move")
+
+	  await makeMove(bob, { game: game_address, timestamp: 111, move_type: "ClaimTimeout" })
+	  t.notEqual(lastResult().Ok, undefined, "Offering a draw didn't reset Alice's clock, so Bob can claim her time ran out")
+	})
+
+	scenario("A game played in turn has no forks to report", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob);
+
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: { Place: { pos: { x: 0, y: 0 } } } })
+	  await makeMove(alice, { game: game_address, timestamp: 1, move_type: { Place: { pos: { x: 1, y: 1 } } } })
+
+	  const integrity = await bob.callSync("main", "get_game_integrity", { game_address })
+	  t.deepEqual(integrity.Ok, { forks: [], double_moves: [] }, "The moves form a single chain")
+
+	  await makeMove(bob, { game: game_address, timestamp: 2, move_type: { ClaimForfeit: { evidence: { author: alice.agentId, base: game_address, moves: [] } } } })
+	  t.equal(lastResult().Ok, undefined, "A forfeit can't be claimed without evidence")
+
+	  // two moves Alice never made, on the base of the one she did
+	  const moves = (await bob.callSync("main", "get_moves", { game_address })).Ok
+	  const base = moves[1].previous_move
+	  const made_up = [{ x: 2, y: 2 }, { x: 0, y: 2 }].map(pos => ({ game: game_address, author: alice.agentId, move_type: { Place: { pos } }, previous_move: base, timestamp: 1 }))
+	  await makeMove(bob, { game: game_address, timestamp: 2, move_type: { ClaimForfeit: { evidence: { author: alice.agentId, base, moves: made_up } } } })
+	  t.equal(lastResult().Ok, undefined, "A forfeit can't be claimed with moves that aren't part of the game")
+	})
+
+	scenario("Two moves made at once fork the game and both are kept", async (s, t, { alice, bob, carol }) => {
+
+	  const teams = [
+	    { name: "crosses", members: [alice.agentId, carol.agentId], turns: "AnyMember" },
+	    { name: "noughts", members: [bob.agentId], turns: "AnyMember" },
+	  ]
+	  const created = await alice.callSync("main", "create_team_game", { teams, timestamp: 0, game_type: "TicTacToe", settings: null })
+	  const game_address = created.Ok
+
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: { Place: { pos: { x: 0, y: 0 } } } })
+
+	  // neither member waits to see the other's move, so both follow Bob's
+	  const [from_alice, from_carol] = await Promise.all([
+	    alice.call("main", "make_move", { new_move: { game: game_address, timestamp: 1, move_type: { Place: { pos: { x: 1, y: 1 } } } } }),
+	    carol.call("main", "make_move", { new_move: { game: game_address, timestamp: 1, move_type: { Place: { pos: { x: 2, y: 2 } } } } }),
+	  ])
+	  t.deepEqual([from_alice.Ok, from_carol.Ok], [null, null], "Both moves were committed")
+	  await s.consistent()
+
+	  const integrity = await bob.callSync("main", "get_game_integrity", { game_address })
+	  t.equal(integrity.Ok.forks.length, 1, "The fork is reported")
+	  t.equal(integrity.Ok.forks[0].discarded.length, 1, "One of the two moves is discarded")
+
+	  const moves = await bob.callSync("main", "get_moves", { game_address })
+	  t.equal(moves.Ok.length, 2, "get_moves follows the canonical branch")
+	  const seen_by_alice = await alice.callSync("main", "get_moves", { game_address })
+	  t.deepEqual(seen_by_alice.Ok, moves.Ok, "Every agent resolves the fork the same way")
+	})
+
+	scenario("Errors carry a machine-readable code", async (s, t, { alice, bob, carol }) => {
+
+	  let game_address = await createGame(alice, bob);
+
+	  await makeMove(carol, { game: game_address, timestamp: 0, move_type: { Place: { pos: { x: 0, y: 0 } } } })
+	  t.equal(JSON.parse(lastResult().Err.Internal).code, "NOT_A_PLAYER", "Carol is not playing in this game")
+
+	  const result = await bob.callSync("main", "get_state", { game_address: bob.agentId })
+	  t.equal(JSON.parse(result.Err.Internal).code, "WRONG_ENTRY_TYPE", "An agent's address is not a game")
+	})
+
+	scenario("A long game is reduced from a snapshot with all of its moves", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob, "TicTacToe", { board_size: 8, win_length: 8 });
+
+	  // a snapshot is taken at the 20th move, the 21st is validated starting from it
+	  for (let i = 0; i < 21; i++) {
+	    const agent = i % 2 === 0 ? bob : alice
+	    await makeMove(agent, { game: game_address, timestamp: i, move_type: { Place: { pos: { x: i % 8, y: Math.floor(i / 8) } } } })
+	    t.notEqual(lastResult().Ok, undefined, `Move ${i} was accepted`)
+	  }
+
+	  const state = (await getState(alice, game_address)).Ok
+	  t.equal(state.moves.length, 21, "The moves before the snapshot are put back into the state")
+	  t.equal(state.game_state.moves.length, 21, "And so are the moves the game state keeps")
+	})
+
+	scenario("The moves of a game can be loaded at once or a page at a time", async (s, t, { alice, bob }) => {
+
+	  let game_address = await createGame(alice, bob);
+
+	  await makeMove(bob, { game: game_address, timestamp: 0, move_type: { Place: { pos: { x: 0, y: 0 } } } })
+	  await makeMove(alice, { game: game_address, timestamp: 1, move_type: { Place: { pos: { x: 1, y: 1 } } } })
+	  await makeMove(bob, { game: game_address, timestamp: 2, move_type: { Place: { pos: { x: 2, y: 2 } } } })
+
+	  const all = await alice.callSync("main", "get_moves", { game_address })
+	  t.deepEqual(all.Ok.map(game_move => game_move.timestamp), [0, 1, 2], "All of the moves are loaded in order")
+
+	  const page = await alice.callSync("main", "get_moves", { game_address, from: 1, limit: 5 })
+	  t.deepEqual(page.Ok.map(game_move => game_move.timestamp), [1, 2], "A page starts at the given index")
+	})
+
+	scenario("Games must be created by one of their players, between different agents, and not in the future", async (s, t, { alice, bob, carol }) => {
+
+	  const teams = [
+	    { name: "crosses", members: [alice.agentId] },
+	    { name: "noughts", members: [bob.agentId] },
+	  ]
+	  const for_others = await carol.callSync("main", "create_team_game", { teams, timestamp: 0, game_type: "TicTacToe", settings: null })
+	  t.equal(for_others.Ok, undefined, "Carol can't create a game between Alice and Bob")
+
+	  const not_an_agent = await alice.callSync("main", "create_game", { opponents: ["QmNotAnAgentAddress"], timestamp: 0, game_type: "TicTacToe", settings: null })
+	  t.equal(not_an_agent.Ok, undefined, "The opponent must be an agent")
+
+	  const against_herself = await alice.callSync("main", "create_game", { opponents: [alice.agentId], timestamp: 0, game_type: "TicTacToe", settings: null })
+	  t.equal(against_herself.Ok, undefined, "Alice can't play against herself")
+
+	  const in_the_future = await alice.callSync("main", "create_game", { opponents: [bob.agentId], timestamp: 4000000000, game_type: "TicTacToe", settings: null })
+	  t.equal(in_the_future.Ok, undefined, "A game can't be created in the future")
+
+	  const created = await alice.callSync("main", "create_game", { opponents: [bob.agentId], timestamp: 0, game_type: "TicTacToe", settings: null })
+	  t.notEqual(created.Ok, undefined, "Alice can create a game with Bob")
+	})
+}
@@ -0,0 +1,75 @@
+/**
+ * All of this code is specific to the game Battleship
+ * By changing the moves, state, reducer and validation rules you can implement you own game.
+ */
+
+pub mod state;
+pub mod validation;
+pub mod moves;
+
+pub use self::{
+    state::{
+        GameState,
+        Settings,
+    },
+    moves::{
+        MoveType,
+    },
+};
+
+use hdk::holochain_persistence_api::cas::content::Address;
+
+use crate::game::Game;
+use crate::game_move::{Move, Outcome};
+use crate::game_type::GameRules;
+
+pub struct Battleship;
+
+impl GameRules for Battleship {
+    type State = GameState;
+    type MoveType = MoveType;
+
+    fn initial(game: &Game) -> GameState {
+        GameState::initial(game)
+    }
+
+    fn evolve(game: &Game, state: &GameState, next_move: &Move, move_type: &MoveType) -> Result<GameState, String> {
+        Ok(state.evolve(game.clone(), next_move, move_type))
+    }
+
+    fn is_valid(game: &Game, state: &GameState, next_move: &Move, move_type: &MoveType) -> Result<(), String> {
+        move_type.is_valid(game.clone(), state.clone(), &next_move.author)
+    }
+
+    fn render(_game: &Game, state: &GameState) -> String {
+        state.render()
+    }
+
+    fn validate_game(game: &Game) -> Result<(), String> {
+        // the game only ends once both fleets are revealed, so without a clock the loser could hold it up forever
+        if game.time_control.is_none() {
+            return Err("Battleship is played with a time control, so a player who won't reveal their fleet loses on time.".into())
+        }
+        game.try_settings::<Settings>()?.is_valid()
+    }
+
+    fn outcome(_game: &Game, state: &GameState) -> Outcome {
+        state.outcome()
+    }
+
+    fn commit(_game: &Game, state: &GameState, author: &Address, key: &str) -> Result<GameState, String> {
+        state.commit(author, key)
+    }
+
+    fn reveal(_game: &Game, state: &GameState, author: &Address, _key: &str, data: &serde_json::Value) -> Result<GameState, String> {
+        Ok(state.reveal(author, data))
+    }
+
+    fn to_move(_game: &Game, state: &GameState) -> Option<Address> {
+        Some(state.to_move.clone())
+    }
+
+    fn describe() -> Vec<MoveType> {
+        MoveType::describe()
+    }
+}
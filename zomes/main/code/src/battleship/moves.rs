@@ -0,0 +1,69 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+
+
+/**
+ *
+ * The MoveType enum defines all the types of moves that are valid in your game and the
+ * data they carry. In Battleship you fire at a square of your opponent's waters and they
+ * report whether the shot hit a ship, missed, or sunk the ship.
+ *
+ * Before the first shot each player commits to their fleet with the meta move
+ * {"Commit": {"key": "fleet", "commitment": <hash_secret of the fleet>}} where the fleet is a list of Ships.
+ * When the game is over both fleets are revealed and checked against every report that was made.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub enum MoveType {
+    Fire {
+        x: usize,
+        y: usize,
+    },
+    Report {
+        result: ShotResult,
+    },
+}
+
+impl MoveType {
+	pub fn describe() -> Vec<MoveType> {
+		vec![
+			MoveType::Fire{x: 0, y: 0},
+			MoveType::Report{result: ShotResult::Hit},
+			MoveType::Report{result: ShotResult::Miss},
+			MoveType::Report{result: ShotResult::Sunk},
+		]
+	}
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub enum ShotResult {
+    Hit,
+    Miss,
+    Sunk,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+/// A ship of the given length whose bow is at (x, y) and which extends right or down from there
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct Ship {
+    pub x: usize,
+    pub y: usize,
+    pub length: usize,
+    pub orientation: Orientation,
+}
+
+impl Ship {
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        (0..self.length).map(|i| match self.orientation {
+            Orientation::Horizontal => (self.x.saturating_add(i), self.y),
+            Orientation::Vertical => (self.x, self.y.saturating_add(i)),
+        }).collect()
+    }
+}
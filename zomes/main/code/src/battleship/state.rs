@@ -0,0 +1,317 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+use hdk::holochain_persistence_api::cas::content::Address;
+use hdk::AGENT_ADDRESS;
+
+use crate::game_move::{Move, Outcome};
+use crate::game::Game;
+use super::{
+    moves::{Ship, ShotResult},
+    MoveType,
+};
+
+pub const FLEET_KEY: &str = "fleet";
+pub const HIT_MARK: char = 'X';
+pub const MISS_MARK: char = 'o';
+pub const SUNK_MARK: char = '#';
+pub const PENDING_MARK: char = '?';
+pub const EMPTY_SPACE: char = ' ';
+pub const MAX_BOARD_SIZE: usize = 26;
+
+/**
+ *
+ * The size of the waters and the lengths of the ships in each fleet are chosen when the game is created.
+ * They default to the classic 10x10 board with ships of 5, 4, 3, 3 and 2 squares.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub board_size: usize,
+    pub fleet: Vec<usize>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            board_size: 10,
+            fleet: vec![5, 4, 3, 3, 2],
+        }
+    }
+}
+
+impl Settings {
+    pub fn is_valid(&self) -> Result<(), String> {
+        if self.board_size == 0 || self.board_size > MAX_BOARD_SIZE {
+            Err(format!("Board size must be between 1 and {}", MAX_BOARD_SIZE))
+        } else if self.fleet.is_empty() {
+            Err("The fleet must have at least one ship".into())
+        } else if self.fleet.iter().any(|length| *length == 0 || *length > self.board_size) {
+            Err("Every ship must be between 1 square and the board size long".into())
+        } else if self.fleet.iter().sum::<usize>() > self.board_size * self.board_size {
+            Err("The fleet does not fit on the board".into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/**
+ *
+ * Each player's fleet is secret until it is revealed at the end of the game, so besides the moves the state
+ * only holds the shots each player has fired and what their opponent reported. Once a fleet is revealed it
+ * is checked against those reports and a player who reported falsely loses the game.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Shot {
+    pub x: usize,
+    pub y: usize,
+    pub result: Option<ShotResult>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct GameState {
+    pub settings: Settings,
+    pub moves: Vec<Move>,
+    pub player_1: PlayerState,
+    pub player_2: PlayerState,
+    pub to_move: Address,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct PlayerState {
+    pub agent: Address,
+    pub committed: bool,
+    /// the shots this player has fired at their opponent
+    pub shots: Vec<Shot>,
+    pub fleet: Option<Vec<Ship>>,
+    pub cheated: bool,
+}
+
+impl PlayerState {
+    pub fn initial(agent: &Address) -> Self {
+        PlayerState {
+            agent: agent.clone(),
+            committed: false,
+            shots: Vec::new(),
+            fleet: None,
+            cheated: false,
+        }
+    }
+
+    pub fn ships_sunk(&self) -> usize {
+        self.shots.iter().filter(|shot| shot.result == Some(ShotResult::Sunk)).count()
+    }
+
+    pub fn pending_shot(&self) -> Option<&Shot> {
+        self.shots.last().filter(|shot| shot.result.is_none())
+    }
+}
+
+impl GameState {
+    pub fn initial(game: &Game) -> Self {
+        GameState {
+            settings: game.settings(),
+            moves: Vec::new(),
+            player_1: PlayerState::initial(&game.players[0]),
+            player_2: PlayerState::initial(&game.players[1]),
+            // by convention player 2 makes the first move thus accepting the invitation to play
+            to_move: game.first_player(),
+        }
+    }
+
+    pub fn player(&self, agent: &Address) -> &PlayerState {
+        if agent == &self.player_1.agent { &self.player_1 } else { &self.player_2 }
+    }
+
+    fn player_mut(&mut self, agent: &Address) -> &mut PlayerState {
+        if agent == &self.player_1.agent { &mut self.player_1 } else { &mut self.player_2 }
+    }
+
+    pub fn opponent(&self, agent: &Address) -> &PlayerState {
+        if agent == &self.player_1.agent { &self.player_2 } else { &self.player_1 }
+    }
+
+    /// The shot fired at a player that they still have to report on
+    pub fn pending_shot(&self) -> Option<&Shot> {
+        self.player_1.pending_shot().or_else(|| self.player_2.pending_shot())
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        let squares = self.settings.board_size * self.settings.board_size;
+        match (self.player_1.cheated, self.player_2.cheated) {
+            (true, true) => return Outcome::Draw,
+            (true, false) => return Outcome::Winner(self.player_2.agent.clone()),
+            (false, true) => return Outcome::Winner(self.player_1.agent.clone()),
+            (false, false) => {},
+        }
+        let fleet_size = self.settings.fleet.len();
+        if self.player_1.ships_sunk() >= fleet_size {
+            Outcome::Winner(self.player_1.agent.clone())
+        } else if self.player_2.ships_sunk() >= fleet_size {
+            Outcome::Winner(self.player_2.agent.clone())
+        } else if self.pending_shot().is_none()
+            && self.player_1.shots.len() == squares
+            && self.player_2.shots.len() == squares {
+            // every square was fired at without sinking the fleet, so someone's reports were false
+            Outcome::Draw
+        } else {
+            Outcome::InProgress
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut disp = "\n".to_string();
+        let me: Address = AGENT_ADDRESS.to_string().into();
+        let is_player = me == self.player_1.agent || me == self.player_2.agent;
+
+        if self.outcome().is_over() {
+            disp.push_str("The game has ended \n");
+        } else if !self.player_1.committed || !self.player_2.committed {
+            disp.push_str("Waiting for both players to commit to their fleet \n");
+        } else if self.to_move == me {
+            match self.pending_shot() {
+                Some(shot) => disp.push_str(&format!("It is your turn, report on the shot at ({}, {}) \n", shot.x, shot.y)),
+                None => disp.push_str("It is your turn, fire! \n"),
+            }
+        } else {
+            disp.push_str("It is your opponents turn \n");
+        }
+
+        // each grid shows the shots fired by one player into their opponent's waters
+        let grids = if is_player {
+            [("Your shots", self.player(&me)), ("Opponent shots", self.opponent(&me))]
+        } else {
+            [("Player 1 shots", &self.player_1), ("Player 2 shots", &self.player_2)]
+        };
+
+        let size = self.settings.board_size;
+        let w = (size - 1).to_string().len();
+        let header: String = (0..size).map(|x| format!("{:<w$} ", x, w = w)).collect();
+        let header = format!("{:w$}  x  {}", "", header.trim_end(), w = w - 1);
+        let width = header.len().max(size * (w + 1) + w + 4);
+        disp.push('\n');
+        disp.push_str(&format!("{:width$}    {}\n", grids[0].0, grids[1].0, width = width));
+        disp.push_str(&format!("{:width$}    {}\n", header, header, width = width));
+        disp.push_str(&format!("{:width$}    y\n", "y", width = width));
+        for y in 0..size {
+            let rows: Vec<String> = grids.iter().map(|(_, shooter)| {
+                let mut row = format!("{:<w$}   |", y, w = w);
+                for x in 0..size {
+                    let c = match shooter.shots.iter().find(|shot| shot.x == x && shot.y == y) {
+                        Some(Shot{result: Some(ShotResult::Hit), ..}) => HIT_MARK,
+                        Some(Shot{result: Some(ShotResult::Miss), ..}) => MISS_MARK,
+                        Some(Shot{result: Some(ShotResult::Sunk), ..}) => SUNK_MARK,
+                        Some(Shot{result: None, ..}) => PENDING_MARK,
+                        None => EMPTY_SPACE,
+                    };
+                    row.push_str(&format!("{:^w$}|", c, w = w));
+                }
+                row
+            }).collect();
+            disp.push_str(&format!("{:width$}    {}\n", rows[0], rows[1], width = width));
+        }
+
+        for player in [&self.player_1, &self.player_2].iter() {
+            if player.cheated {
+                disp.push_str(&format!("{} reported falsely on their fleet!\n", if player.agent == me { "You" } else { "Your opponent" }));
+            }
+        }
+        disp
+    }
+
+    pub fn evolve(&self, game: Game, next_move: &Move, move_type: &MoveType) -> Self {
+        let mut state = self.clone();
+        state.moves.push(next_move.to_owned());
+
+        match move_type {
+            MoveType::Fire{x, y} => {
+                state.player_mut(&next_move.author).shots.push(Shot{x: *x, y: *y, result: None});
+                // the opponent must report on the shot
+                state.to_move = game.next_player(&next_move.author);
+            },
+            MoveType::Report{result} => {
+                let shooter = game.next_player(&next_move.author);
+                if let Some(shot) = state.player_mut(&shooter).shots.last_mut() {
+                    shot.result = Some(*result);
+                }
+                // having reported, it is now this player's turn to fire
+                state.to_move = next_move.author.clone();
+            },
+        }
+        state
+    }
+
+    pub fn commit(&self, author: &Address, key: &str) -> Result<Self, String> {
+        if key != FLEET_KEY {
+            return Err(format!("Battleship only takes a commitment to your fleet, use the key \"{}\".", FLEET_KEY))
+        }
+        if !self.player_1.shots.is_empty() || !self.player_2.shots.is_empty() {
+            return Err("Fleets must be committed before the first shot.".into())
+        }
+        let mut state = self.clone();
+        state.player_mut(author).committed = true;
+        Ok(state)
+    }
+
+    /// Record a revealed fleet, marking the player as a cheat if it is not a legal fleet or doesn't match their reports
+    pub fn reveal(&self, author: &Address, data: &serde_json::Value) -> Self {
+        let mut state = self.clone();
+        let fleet: Option<Vec<Ship>> = serde_json::from_value(data.clone()).ok();
+        let honest = fleet.as_ref().map_or(false, |fleet| {
+            is_valid_fleet(fleet, &self.settings).is_ok()
+                && reports_match_fleet(fleet, &self.opponent(author).shots)
+        });
+        let player = state.player_mut(author);
+        player.fleet = fleet;
+        player.cheated = !honest;
+        state
+    }
+}
+
+/*========================================
+=            Helper functions            =
+========================================*/
+
+/// Check that a fleet has exactly the ships from the settings, all on the board and not overlapping
+pub fn is_valid_fleet(fleet: &[Ship], settings: &Settings) -> Result<(), String> {
+    let mut lengths: Vec<usize> = fleet.iter().map(|ship| ship.length).collect();
+    let mut expected = settings.fleet.clone();
+    lengths.sort();
+    expected.sort();
+    if lengths != expected {
+        return Err(format!("The fleet must have ships of lengths {:?}", settings.fleet))
+    }
+    let cells: Vec<(usize, usize)> = fleet.iter().flat_map(|ship| ship.cells()).collect();
+    if cells.iter().any(|(x, y)| *x >= settings.board_size || *y >= settings.board_size) {
+        return Err("Every ship must be on the board".into())
+    }
+    if cells.iter().enumerate().any(|(i, cell)| cells[i + 1..].contains(cell)) {
+        return Err("Ships cannot overlap".into())
+    }
+    Ok(())
+}
+
+/// Check every report made on the shots fired at a fleet was truthful
+pub fn reports_match_fleet(fleet: &[Ship], shots_received: &[Shot]) -> bool {
+    shots_received.iter().enumerate().all(|(i, shot)| {
+        let result = match shot.result {
+            Some(result) => result,
+            None => return true,
+        };
+        let truth = match fleet.iter().find(|ship| ship.cells().contains(&(shot.x, shot.y))) {
+            None => ShotResult::Miss,
+            Some(ship) => {
+                // the ship is sunk by the shot that hits the last of its squares
+                let hit_so_far = |cell: &(usize, usize)| shots_received[..=i].iter().any(|earlier| (earlier.x, earlier.y) == *cell);
+                if ship.cells().iter().all(hit_so_far) { ShotResult::Sunk } else { ShotResult::Hit }
+            },
+        };
+        result == truth
+    })
+}
+
+/*=====  End of Helper functions  ======*/
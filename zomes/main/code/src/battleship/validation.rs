@@ -0,0 +1,51 @@
+use hdk::holochain_persistence_api::{
+    cas::content::Address,
+};
+
+use crate::game::Game;
+use crate::game_type::is_players_turn;
+use super::{
+    Battleship,
+    GameState,
+    MoveType,
+};
+
+
+/**
+ *
+ * Shots can only be fired once both fleets are committed, and every shot has to be reported on by
+ * the player it was fired at before the next one. Whether a report is truthful can't be known until
+ * the fleet is revealed, see `GameState::reveal`.
+ *
+ */
+
+
+impl MoveType {
+    pub fn is_valid(&self, game: Game, game_state: GameState, author: &Address) -> Result<(), String> {
+        if !game_state.player_1.committed || !game_state.player_2.committed {
+            return Err("Both players must commit to their fleet before the first shot.".into())
+        }
+        match self {
+            MoveType::Fire{x, y} => {
+                if game_state.pending_shot().is_some() {
+                    return Err("The last shot must be reported on before firing again.".into())
+                }
+                is_players_turn::<Battleship>(&game, &game_state, author)?;
+                let size = game_state.settings.board_size;
+                if *x >= size || *y >= size { // no need to check > 0 as usize is always positive
+                    return Err("Position is not in bounds".into())
+                }
+                if game_state.player(author).shots.iter().any(|shot| shot.x == *x && shot.y == *y) {
+                    return Err("You have already fired at that position.".into())
+                }
+                Ok(())
+            },
+            MoveType::Report{result: _} => {
+                if game_state.pending_shot().is_none() {
+                    return Err("There is no shot to report on.".into())
+                }
+                is_players_turn::<Battleship>(&game, &game_state, author)
+            },
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+
+
+/**
+ *
+ * The MoveType enum defines all the types of moves that are valid in your game and the 
+ * data they carry. In Checkers you can move a piece (MovePiece) from a location to another location.
+ *
+ * A capture is a MovePiece that jumps two squares. When a chain of captures is possible
+ * each jump is its own move and the player keeps the turn until the chain is finished.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub enum MoveType {
+    MovePiece {
+        from: Piece,
+        to: Piece,
+    },
+}
+
+impl MoveType {
+	pub fn describe() -> Vec<MoveType> {
+		vec![
+			MoveType::MovePiece{from: Piece{x: 0, y: 0}, to: Piece{x: 1, y: 1}}
+		]
+	}
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct Piece {
+    pub x: usize,
+    pub y: usize,
+}
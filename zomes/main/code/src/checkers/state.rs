@@ -0,0 +1,273 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+use hdk::holochain_persistence_api::cas::content::Address;
+use hdk::AGENT_ADDRESS;
+
+use crate::game_move::{Move, Outcome};
+use crate::game::Game;
+use super::{
+    moves::Piece,
+    MoveType,
+    validation::{Player, get_current_player},
+};
+
+pub const BOARD_SIZE: usize = 8;
+pub const PLAYER_1_MARK: char = 'o';
+pub const PLAYER_1_KING_MARK: char = 'O';
+pub const PLAYER_2_MARK: char = 'x';  //player 2 / Xs go first
+pub const PLAYER_2_KING_MARK: char = 'X';
+pub const EMPTY_SPACE: char = ' ';
+
+/// The contents of a square in the dense board representation
+pub const EMPTY: u8 = 0;
+pub const PLAYER_1_MAN: u8 = 1;
+pub const PLAYER_2_MAN: u8 = 2;
+pub const PLAYER_1_KING: u8 = 3;
+pub const PLAYER_2_KING: u8 = 4;
+
+pub type Board = [[u8; BOARD_SIZE]; BOARD_SIZE];
+
+/**
+ *
+ * The state holds the moves made so far and the men and kings of each player. Because a chain of
+ * captures is made up of several moves by the same player, it also keeps track of whose turn it is
+ * and which piece (if any) has to keep capturing.
+ *
+ */
+
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct GameState {
+    pub moves: Vec<Move>,
+    pub player_1: PlayerState,
+    pub player_2: PlayerState,
+    pub to_move: Address,
+    pub jumping_piece: Option<Piece>,
+    pub outcome: Outcome,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct PlayerState {
+    pub pieces: Vec<Piece>,
+    pub kings: Vec<Piece>,
+}
+
+impl PlayerState {
+    pub fn initial(rows: std::ops::Range<usize>) -> Self {
+        // pieces start on the dark squares of their first three rows
+        let pieces = rows
+            .flat_map(|y| (0..BOARD_SIZE).map(move |x| Piece{x, y}))
+            .filter(|piece| (piece.x + piece.y) % 2 == 0)
+            .collect();
+        PlayerState {
+            pieces,
+            kings: Vec::new(),
+        }
+    }
+}
+
+impl GameState {
+    pub fn initial(game: &Game) -> Self {
+        GameState {
+            moves: Vec::new(),
+            player_1: PlayerState::initial(0..3),
+            player_2: PlayerState::initial(BOARD_SIZE - 3..BOARD_SIZE),
+            // by convention player 2 makes the first move thus accepting the invitation to play
+            to_move: game.first_player(),
+            jumping_piece: None,
+            outcome: Outcome::InProgress,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut disp = "\n".to_string();
+
+        if self.outcome.is_over() {
+            disp.push_str("The game has ended \n");
+        } else if self.to_move.to_string() == AGENT_ADDRESS.to_string() {
+            match &self.jumping_piece {
+                Some(piece) => disp.push_str(&format!("It is your turn, continue capturing with the piece at ({}, {}) \n", piece.x, piece.y)),
+                None => disp.push_str("It is your turn \n"),
+            }
+        } else if self.moves.is_empty() {
+            disp.push_str("Your opponent moves first \n");
+        } else {
+            disp.push_str("It is your opponents turn \n");
+        }
+        disp.push('\n');
+        disp.push_str("  x  0 1 2 3 4 5 6 7\ny\n");
+        let board = board_sparse_to_dense(self);
+        for y in 0..BOARD_SIZE {
+            disp.push_str(&format!("{}   |", y));
+            for column in board.iter() {
+                let c = match column[y] {
+                    PLAYER_1_MAN => PLAYER_1_MARK,
+                    PLAYER_1_KING => PLAYER_1_KING_MARK,
+                    PLAYER_2_MAN => PLAYER_2_MARK,
+                    PLAYER_2_KING => PLAYER_2_KING_MARK,
+                    _ => EMPTY_SPACE,
+                };
+                disp.push_str(&format!("{}|", c));
+            }
+            disp.push('\n');
+        }
+        disp
+    }
+
+    pub fn evolve(&self, game: Game, next_move: &Move, move_type: &MoveType) -> Result<Self, String> {
+        let current_player = get_current_player(&game, &next_move.author)?;
+
+        match move_type {
+            MoveType::MovePiece{from, to} => {
+                let mut board = board_sparse_to_dense(self);
+                let mut moves = self.moves.clone();
+                moves.push(next_move.to_owned());
+
+                // move the piece, removing any piece that was jumped over
+                let square = board[from.x][from.y];
+                board[from.x][from.y] = EMPTY;
+                let captured = is_capture(from, to);
+                if captured {
+                    board[(from.x + to.x) / 2][(from.y + to.y) / 2] = EMPTY;
+                }
+
+                // a man reaching the far side is crowned, which ends the turn
+                let crowned = square == current_player.man() && to.y == current_player.crowning_row();
+                board[to.x][to.y] = if crowned { current_player.king() } else { square };
+
+                // after a capture the same piece must keep capturing if it can
+                let keep_jumping = captured && !crowned && !piece_moves(&board, to).0.is_empty();
+                let (to_move, jumping_piece, next_player) = if keep_jumping {
+                    (next_move.author.clone(), Some(to.clone()), current_player)
+                } else {
+                    (game.next_player(&next_move.author), None, current_player.opponent())
+                };
+
+                // a player who has no pieces left or cannot move loses
+                let outcome = if legal_moves(&board, next_player, &jumping_piece).is_empty() {
+                    Outcome::Winner(game.next_player(&to_move))
+                } else {
+                    Outcome::InProgress
+                };
+
+                let (player_1, player_2) = board_dense_to_sparse(board);
+
+                Ok(GameState{
+                    player_1,
+                    player_2,
+                    moves,
+                    to_move,
+                    jumping_piece,
+                    outcome,
+                })
+            }
+        }
+    }
+
+}
+
+/*========================================
+=            Helper functions            =
+========================================*/
+
+pub fn board_sparse_to_dense(state: &GameState) -> Board {
+    let mut board = [[EMPTY; BOARD_SIZE]; BOARD_SIZE];
+    state.player_1.pieces.iter().for_each(|piece| {
+        board[piece.x][piece.y] = PLAYER_1_MAN;
+    });
+    state.player_1.kings.iter().for_each(|piece| {
+        board[piece.x][piece.y] = PLAYER_1_KING;
+    });
+    state.player_2.pieces.iter().for_each(|piece| {
+        board[piece.x][piece.y] = PLAYER_2_MAN;
+    });
+    state.player_2.kings.iter().for_each(|piece| {
+        board[piece.x][piece.y] = PLAYER_2_KING;
+    });
+    board
+}
+
+pub fn board_dense_to_sparse(board: Board) -> (PlayerState, PlayerState) {
+    let mut player_1 = PlayerState { pieces: Vec::new(), kings: Vec::new() };
+    let mut player_2 = PlayerState { pieces: Vec::new(), kings: Vec::new() };
+    board.iter().enumerate().for_each(|(x, row)| {
+        row.iter().enumerate().for_each(|(y, square)| {
+            match *square {
+                PLAYER_1_MAN => player_1.pieces.push(Piece{x, y}),
+                PLAYER_1_KING => player_1.kings.push(Piece{x, y}),
+                PLAYER_2_MAN => player_2.pieces.push(Piece{x, y}),
+                PLAYER_2_KING => player_2.kings.push(Piece{x, y}),
+                _ => {},
+            }
+        })
+    });
+    (player_1, player_2)
+}
+
+pub fn is_capture(from: &Piece, to: &Piece) -> bool {
+    (from.x as i64 - to.x as i64).abs() == 2
+}
+
+/// The captures and the simple moves the piece on the given square can make
+pub fn piece_moves(board: &Board, from: &Piece) -> (Vec<Piece>, Vec<Piece>) {
+    let square = board[from.x][from.y];
+    let player = match Player::owning(square) {
+        Some(player) => player,
+        None => return (Vec::new(), Vec::new()),
+    };
+    let forward = player.forward();
+    let directions: Vec<(i64, i64)> = if square == player.king() {
+        vec![(-1, forward), (1, forward), (-1, -forward), (1, -forward)]
+    } else {
+        vec![(-1, forward), (1, forward)]
+    };
+
+    let on_board = |x: i64, y: i64| x >= 0 && y >= 0 && x < BOARD_SIZE as i64 && y < BOARD_SIZE as i64;
+    let mut captures = Vec::new();
+    let mut steps = Vec::new();
+    for (dx, dy) in directions {
+        let (x, y) = (from.x as i64 + dx, from.y as i64 + dy);
+        if !on_board(x, y) {
+            continue;
+        }
+        let neighbour = board[x as usize][y as usize];
+        if neighbour == EMPTY {
+            steps.push(Piece{x: x as usize, y: y as usize});
+        } else if Player::owning(neighbour) == Some(player.opponent()) {
+            let (jump_x, jump_y) = (x + dx, y + dy);
+            if on_board(jump_x, jump_y) && board[jump_x as usize][jump_y as usize] == EMPTY {
+                captures.push(Piece{x: jump_x as usize, y: jump_y as usize});
+            }
+        }
+    }
+    (captures, steps)
+}
+
+/// All of the (from, to) moves the player may make. Captures are compulsory so if any
+/// capture is possible only captures are returned.
+pub fn legal_moves(board: &Board, player: Player, jumping_piece: &Option<Piece>) -> Vec<(Piece, Piece)> {
+    let pieces: Vec<Piece> = match jumping_piece {
+        Some(piece) => vec![piece.clone()],
+        None => (0..BOARD_SIZE)
+            .flat_map(|x| (0..BOARD_SIZE).map(move |y| Piece{x, y}))
+            .filter(|piece| Player::owning(board[piece.x][piece.y]) == Some(player))
+            .collect(),
+    };
+
+    let mut captures = Vec::new();
+    let mut steps = Vec::new();
+    for from in pieces {
+        let (piece_captures, piece_steps) = piece_moves(board, &from);
+        captures.extend(piece_captures.into_iter().map(|to| (from.clone(), to)));
+        steps.extend(piece_steps.into_iter().map(|to| (from.clone(), to)));
+    }
+
+    if !captures.is_empty() || jumping_piece.is_some() {
+        captures
+    } else {
+        steps
+    }
+}
+
+/*=====  End of Helper functions  ======*/
@@ -0,0 +1,150 @@
+use hdk::holochain_persistence_api::{
+    cas::content::Address,
+};
+
+use crate::game::Game;
+use crate::game_type::is_players_turn;
+use super::{
+    Checkers,
+    GameState,
+    moves::Piece,
+    MoveType,
+    state::{
+        board_sparse_to_dense, is_capture, legal_moves, Board, BOARD_SIZE, EMPTY,
+        PLAYER_1_MAN, PLAYER_1_KING, PLAYER_2_MAN, PLAYER_2_KING,
+    },
+};
+
+
+/**
+ *
+ * Pieces move in turn, diagonally forward or as kings in any diagonal direction. Captures are compulsory
+ * and a piece that captured must keep capturing if it can before the turn passes.
+ *
+ */
+
+
+impl MoveType {
+    pub fn is_valid(&self, game: Game, game_state: GameState, author: &Address) -> Result<(), String> {
+        match self {
+            MoveType::MovePiece{from, to} => {
+                let player = get_current_player(&game, author)?;
+                is_players_turn::<Checkers>(&game, &game_state, author)?;
+                from.is_in_bounds()?;
+                to.is_in_bounds()?;
+                let board = board_sparse_to_dense(&game_state);
+                from.is_players_piece(&board, player)?;
+                to.is_empty(&board)?;
+                if let Some(jumping_piece) = &game_state.jumping_piece {
+                    if from != jumping_piece {
+                        return Err(format!("The piece at ({}, {}) must continue capturing.", jumping_piece.x, jumping_piece.y))
+                    }
+                }
+
+                let legal = legal_moves(&board, player, &game_state.jumping_piece);
+                if legal.contains(&(from.clone(), to.clone())) {
+                    Ok(())
+                } else if legal.iter().any(|(from, to)| is_capture(from, to)) {
+                    Err("A capture is available and must be taken.".into())
+                } else {
+                    Err("Pieces move diagonally forward one square or jump over an opponent's piece. Only kings can move backwards.".into())
+                }
+            }
+        }
+    }
+}
+
+
+/*========================================
+=            Helper functions            =
+========================================*/
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Player{
+    Player1,
+    Player2,
+}
+
+impl Player {
+    /// The player who owns the piece on a square, if there is one
+    pub fn owning(square: u8) -> Option<Player> {
+        match square {
+            PLAYER_1_MAN | PLAYER_1_KING => Some(Player::Player1),
+            PLAYER_2_MAN | PLAYER_2_KING => Some(Player::Player2),
+            _ => None,
+        }
+    }
+
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::Player1 => Player::Player2,
+            Player::Player2 => Player::Player1,
+        }
+    }
+
+    pub fn man(self) -> u8 {
+        match self {
+            Player::Player1 => PLAYER_1_MAN,
+            Player::Player2 => PLAYER_2_MAN,
+        }
+    }
+
+    pub fn king(self) -> u8 {
+        match self {
+            Player::Player1 => PLAYER_1_KING,
+            Player::Player2 => PLAYER_2_KING,
+        }
+    }
+
+    /// Player 1 starts at the top of the board and moves down (increasing y)
+    pub fn forward(self) -> i64 {
+        match self {
+            Player::Player1 => 1,
+            Player::Player2 => -1,
+        }
+    }
+
+    pub fn crowning_row(self) -> usize {
+        match self {
+            Player::Player1 => BOARD_SIZE - 1,
+            Player::Player2 => 0,
+        }
+    }
+}
+
+pub fn get_current_player(game: &Game, player_addr: &Address) -> Result<Player, String> {
+    match game.player_index(player_addr) {
+        Some(0) => Ok(Player::Player1),
+        Some(1) => Ok(Player::Player2),
+        _ => Err("Player is not part of this game!".into()),
+    }
+}
+
+
+impl Piece {
+    fn is_in_bounds(&self) -> Result<(), String> {
+        if self.x < BOARD_SIZE
+        && self.y < BOARD_SIZE // no need to check > 0 as usize is always positive
+        {
+            Ok(())
+        } else {
+            Err("Position is not in bounds".to_string())
+        }
+    }
+
+    fn is_empty(&self, board: &Board) -> Result<(), String> {
+        match board[self.x][self.y] == EMPTY {
+            true => Ok(()),
+            false => Err("A piece already exists at that position.".to_string())
+        }
+    }
+
+    fn is_players_piece(&self, board: &Board, player: Player) -> Result<(), String> {
+        match Player::owning(board[self.x][self.y]) == Some(player) {
+            true => Ok(()),
+            false => Err("You do not have a piece at that position.".to_string())
+        }
+    }
+}
+
+/*=====  End of Helper functions  ======*/
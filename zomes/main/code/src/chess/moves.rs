@@ -0,0 +1,58 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+
+use super::position::ChessMove;
+
+
+/**
+ *
+ * The MoveType enum defines all the types of moves that are valid in your game and the
+ * data they carry. In Chess you move a piece from one square to another.
+ *
+ * Squares use x for the file (0 = a, 7 = h) and y for the rank (0 = rank 1, 7 = rank 8).
+ * Castling is written as the king moving two squares and a pawn reaching the last rank
+ * must say which piece it is promoted to.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub enum MoveType {
+    MovePiece {
+        from: Square,
+        to: Square,
+        #[serde(default)]
+        promotion: Option<PieceKind>,
+    },
+}
+
+impl MoveType {
+	pub fn describe() -> Vec<MoveType> {
+		vec![
+			MoveType::MovePiece{from: Square{x: 4, y: 1}, to: Square{x: 4, y: 3}, promotion: None},
+			MoveType::MovePiece{from: Square{x: 0, y: 6}, to: Square{x: 0, y: 7}, promotion: Some(PieceKind::Queen)},
+		]
+	}
+
+	pub fn to_chess_move(&self) -> ChessMove {
+		match self {
+			MoveType::MovePiece{from, to, promotion} => ChessMove{from: *from, to: *to, promotion: *promotion},
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct Square {
+    pub x: usize,
+    pub y: usize,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum PieceKind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
@@ -0,0 +1,201 @@
+use crate::game_move::Outcome;
+use crate::game::Game;
+use super::{
+    moves::PieceKind,
+    position::{ChessMove, Colour, Position, START_FEN, file_name, rank_name, square_name, parse_square, piece_letter, piece_from_char},
+    state::{GameState, Settings},
+};
+
+/**
+ *
+ * Portable Game Notation lets a chess game be exported to, or started from, any other chess software.
+ * Moves in PGN are written in standard algebraic notation (SAN) such as "Nf3", "exd5" or "O-O".
+ *
+ */
+
+impl Position {
+    /// The standard algebraic notation of a legal move
+    pub fn san(&self, chess_move: &ChessMove) -> String {
+        let piece = match self.piece_at(chess_move.from) {
+            Some(piece) => piece,
+            None => return square_name(chess_move.to),
+        };
+        let (from, to) = (chess_move.from, chess_move.to);
+        let capture = if self.is_capture(chess_move) { "x" } else { "" };
+
+        let mut san = match piece.kind {
+            PieceKind::King if (to.x as i64 - from.x as i64).abs() == 2 => {
+                if to.x == 6 { "O-O".to_string() } else { "O-O-O".to_string() }
+            },
+            PieceKind::Pawn => {
+                let file = if capture.is_empty() { String::new() } else { file_name(from.x).to_string() };
+                let promotion = chess_move.promotion.map_or(String::new(), |kind| format!("={}", piece_letter(kind)));
+                format!("{}{}{}{}", file, capture, square_name(to), promotion)
+            },
+            kind => {
+                // name the file, the rank or both if another piece of the same kind could also move there
+                let others: Vec<ChessMove> = self.legal_moves().into_iter()
+                    .filter(|other| other.to == to && other.from != from && self.piece_at(other.from) == Some(piece))
+                    .collect();
+                let disambiguation = if others.is_empty() {
+                    String::new()
+                } else if others.iter().all(|other| other.from.x != from.x) {
+                    file_name(from.x).to_string()
+                } else if others.iter().all(|other| other.from.y != from.y) {
+                    rank_name(from.y).to_string()
+                } else {
+                    square_name(from)
+                };
+                format!("{}{}{}{}", piece_letter(kind), disambiguation, capture, square_name(to))
+            },
+        };
+
+        let next = self.apply(chess_move);
+        if next.in_check(next.to_move) {
+            san.push(if next.legal_moves().is_empty() { '#' } else { '+' });
+        }
+        san
+    }
+
+    /// Find the legal move written in algebraic notation. Coordinate notation such as "e2e4" or "e7e8q" is also accepted.
+    pub fn parse_san(&self, text: &str) -> Result<ChessMove, String> {
+        let normalize = |san: &str| {
+            san.trim()
+                .trim_end_matches(|c| "+#!?".contains(c))
+                .trim_end_matches("e.p.")
+                .replace("0-0-0", "O-O-O")
+                .replace("0-0", "O-O")
+                .replace('=', "")
+        };
+        let wanted = normalize(text);
+        let legal = self.legal_moves();
+
+        if wanted.len() == 4 || wanted.len() == 5 {
+            if let (Some(from), Some(to)) = (wanted.get(0..2).and_then(parse_square), wanted.get(2..4).and_then(parse_square)) {
+                let promotion = wanted.chars().nth(4).and_then(piece_from_char).map(|piece| piece.kind);
+                let chess_move = ChessMove{from, to, promotion};
+                if legal.contains(&chess_move) {
+                    return Ok(chess_move)
+                }
+            }
+        }
+
+        legal.into_iter()
+            .find(|chess_move| normalize(&self.san(chess_move)) == wanted)
+            .ok_or_else(|| format!("{} is not a legal move in this position", text.trim()))
+    }
+}
+
+impl GameState {
+    /// The numbered moves of the game, e.g. "1. e4 e5 2. Nf3"
+    pub fn movetext(&self) -> String {
+        let start = Position::from_fen(&self.initial_fen).unwrap_or_else(|_| Position::start());
+        let mut number = start.fullmove_number;
+        let mut white_to_move = start.to_move == Colour::White;
+        let mut tokens = Vec::new();
+        for (i, san) in self.san_moves.iter().enumerate() {
+            if white_to_move {
+                tokens.push(format!("{}.", number));
+            } else if i == 0 {
+                tokens.push(format!("{}...", number));
+            }
+            tokens.push(san.clone());
+            if !white_to_move {
+                number += 1;
+            }
+            white_to_move = !white_to_move;
+        }
+        tokens.join(" ")
+    }
+
+    /// The PGN result of the game. The outcome comes from the framework so resignations are included.
+    pub fn pgn_result(&self, outcome: &Outcome) -> &'static str {
+        match outcome {
+            Outcome::Winner(winner) if winner == &self.white => "1-0",
+            Outcome::Winner(_) => "0-1",
+            Outcome::Draw => "1/2-1/2",
+            _ => "*",
+        }
+    }
+}
+
+/// Export a game with all of the moves made so far
+pub fn to_pgn(game: &Game, state: &GameState, outcome: &Outcome) -> String {
+    let mut tags = vec![
+        ("Event", "Holochain chess game".to_string()),
+        ("Site", "?".to_string()),
+        ("Date", {
+            let (year, month, day) = game.created_date();
+            format!("{:04}.{:02}.{:02}", year, month, day)
+        }),
+        ("Round", "-".to_string()),
+        ("White", state.white.to_string()),
+        ("Black", state.black.to_string()),
+        ("Result", state.pgn_result(outcome).to_string()),
+    ];
+    if state.initial_fen != Position::start().to_fen() {
+        tags.push(("SetUp", "1".to_string()));
+        tags.push(("FEN", state.initial_fen.clone()));
+    }
+
+    let mut pgn: String = tags.iter()
+        .map(|(name, value)| format!("[{} \"{}\"]\n", name, value))
+        .collect();
+    pgn.push('\n');
+    let movetext = state.movetext();
+    if !movetext.is_empty() {
+        pgn.push_str(&movetext);
+        pgn.push(' ');
+    }
+    pgn.push_str(state.pgn_result(outcome));
+    pgn.push('\n');
+    pgn
+}
+
+/// Read the starting position and the moves of a PGN into the settings for a new game which continues from it
+pub fn parse_pgn(pgn: &str) -> Result<Settings, String> {
+    let mut fen = None;
+    let mut movetext = String::new();
+    for line in pgn.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            let mut parts = line.trim_matches(|c| c == '[' || c == ']').splitn(2, ' ');
+            let name = parts.next().unwrap_or("");
+            let value = parts.next().unwrap_or("").trim().trim_matches('"');
+            if name == "FEN" {
+                fen = Some(value.to_string());
+            }
+        } else if !line.starts_with('%') {
+            // a semicolon starts a comment that runs to the end of the line
+            movetext.push_str(line.split(';').next().unwrap_or(""));
+            movetext.push(' ');
+        }
+    }
+
+    // drop {comments} and (variations), which may be nested
+    let mut depth = 0;
+    let mainline: String = movetext.chars().filter(|c| {
+        match c {
+            '{' | '(' => { depth += 1; false },
+            '}' | ')' => { depth -= 1; false },
+            _ => depth == 0,
+        }
+    }).collect();
+
+    let opening = mainline.split_whitespace()
+        .filter(|token| !token.starts_with('$') && !["1-0", "0-1", "1/2-1/2", "*"].contains(token))
+        .map(|token| {
+            // move numbers may be attached to the move, as in "1.e4" or "12...Nf6"
+            let unnumbered = token.trim_start_matches(|c: char| c.is_ascii_digit());
+            if unnumbered.starts_with('.') { unnumbered.trim_start_matches('.') } else { token }.to_string()
+        })
+        .filter(|token| !token.is_empty())
+        .collect();
+
+    let settings = Settings {
+        fen: fen.filter(|fen| fen != START_FEN),
+        opening,
+    };
+    settings.is_valid()?;
+    Ok(settings)
+}
@@ -0,0 +1,496 @@
+use super::moves::{Square, PieceKind};
+
+pub const BOARD_SIZE: usize = 8;
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/**
+ *
+ * The rules of chess. A Position is everything needed to know which moves are legal,
+ * which is exactly what a FEN string describes.
+ *
+ */
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Colour {
+    White,
+    Black,
+}
+
+impl Colour {
+    pub fn opponent(self) -> Colour {
+        match self {
+            Colour::White => Colour::Black,
+            Colour::Black => Colour::White,
+        }
+    }
+
+    /// The direction pawns move in
+    fn forward(self) -> i64 {
+        match self {
+            Colour::White => 1,
+            Colour::Black => -1,
+        }
+    }
+
+    fn home_rank(self) -> usize {
+        match self {
+            Colour::White => 0,
+            Colour::Black => BOARD_SIZE - 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ChessPiece {
+    pub colour: Colour,
+    pub kind: PieceKind,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Castling {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+/// A move as understood by the rules, independent of the entry it was written in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChessMove {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<PieceKind>,
+}
+
+pub type Board = [[Option<ChessPiece>; BOARD_SIZE]; BOARD_SIZE];
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Position {
+    /// indexed as board[x][y]
+    pub board: Board,
+    pub to_move: Colour,
+    pub castling: Castling,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+const KNIGHT_STEPS: [(i64, i64); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING_STEPS: [(i64, i64); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+const ROOK_DIRECTIONS: [(i64, i64); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const BISHOP_DIRECTIONS: [(i64, i64); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+const PROMOTIONS: [PieceKind; 4] = [PieceKind::Queen, PieceKind::Rook, PieceKind::Bishop, PieceKind::Knight];
+
+impl Position {
+    pub fn start() -> Self {
+        Position::from_fen(START_FEN).unwrap()
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<ChessPiece> {
+        self.board[square.x][square.y]
+    }
+
+    /// The square a number of steps away, if it is on the board
+    fn offset(square: Square, dx: i64, dy: i64) -> Option<Square> {
+        let (x, y) = (square.x as i64 + dx, square.y as i64 + dy);
+        if x >= 0 && y >= 0 && x < BOARD_SIZE as i64 && y < BOARD_SIZE as i64 {
+            Some(Square{x: x as usize, y: y as usize})
+        } else {
+            None
+        }
+    }
+
+    fn squares() -> impl Iterator<Item = Square> {
+        (0..BOARD_SIZE).flat_map(|y| (0..BOARD_SIZE).map(move |x| Square{x, y}))
+    }
+
+    pub fn king_square(&self, colour: Colour) -> Option<Square> {
+        Position::squares().find(|square| self.piece_at(*square) == Some(ChessPiece{colour, kind: PieceKind::King}))
+    }
+
+    /// True if a piece of the given colour could capture on the square
+    pub fn is_attacked(&self, square: Square, by: Colour) -> bool {
+        let is = |target: Option<Square>, kinds: &[PieceKind]| {
+            target.and_then(|target| self.piece_at(target))
+                .map_or(false, |piece| piece.colour == by && kinds.contains(&piece.kind))
+        };
+        // a pawn attacks the squares diagonally in front of it
+        let pawn_attack = [-1, 1].iter().any(|dx| is(Position::offset(square, *dx, -by.forward()), &[PieceKind::Pawn]));
+        let knight_attack = KNIGHT_STEPS.iter().any(|(dx, dy)| is(Position::offset(square, *dx, *dy), &[PieceKind::Knight]));
+        let king_attack = KING_STEPS.iter().any(|(dx, dy)| is(Position::offset(square, *dx, *dy), &[PieceKind::King]));
+        let slider_attack = |directions: &[(i64, i64)], kinds: &[PieceKind]| {
+            directions.iter().any(|(dx, dy)| {
+                let mut current = Position::offset(square, *dx, *dy);
+                while let Some(target) = current {
+                    if self.piece_at(target).is_some() {
+                        return is(Some(target), kinds)
+                    }
+                    current = Position::offset(target, *dx, *dy);
+                }
+                false
+            })
+        };
+        pawn_attack
+            || knight_attack
+            || king_attack
+            || slider_attack(&ROOK_DIRECTIONS, &[PieceKind::Rook, PieceKind::Queen])
+            || slider_attack(&BISHOP_DIRECTIONS, &[PieceKind::Bishop, PieceKind::Queen])
+    }
+
+    pub fn in_check(&self, colour: Colour) -> bool {
+        self.king_square(colour).map_or(false, |square| self.is_attacked(square, colour.opponent()))
+    }
+
+    /// Moves that follow the movement rules of each piece but may leave the king in check
+    pub fn pseudo_legal_moves(&self) -> Vec<ChessMove> {
+        let colour = self.to_move;
+        let mut moves = Vec::new();
+        let add = |from: Square, to: Square, moves: &mut Vec<ChessMove>| {
+            moves.push(ChessMove{from, to, promotion: None});
+        };
+
+        for from in Position::squares() {
+            let piece = match self.piece_at(from) {
+                Some(piece) if piece.colour == colour => piece,
+                _ => continue,
+            };
+            match piece.kind {
+                PieceKind::Pawn => {
+                    let last_rank = colour.opponent().home_rank();
+                    let mut pawn_moves = Vec::new();
+                    if let Some(one) = Position::offset(from, 0, colour.forward()) {
+                        if self.piece_at(one).is_none() {
+                            pawn_moves.push(one);
+                            let start_rank = (colour.home_rank() as i64 + colour.forward()) as usize;
+                            if from.y == start_rank {
+                                if let Some(two) = Position::offset(one, 0, colour.forward()) {
+                                    if self.piece_at(two).is_none() {
+                                        pawn_moves.push(two);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    for dx in [-1, 1].iter() {
+                        if let Some(target) = Position::offset(from, *dx, colour.forward()) {
+                            let enemy = self.piece_at(target).map_or(false, |other| other.colour != colour);
+                            if enemy || self.en_passant == Some(target) {
+                                pawn_moves.push(target);
+                            }
+                        }
+                    }
+                    for to in pawn_moves {
+                        if to.y == last_rank {
+                            for kind in PROMOTIONS.iter() {
+                                moves.push(ChessMove{from, to, promotion: Some(*kind)});
+                            }
+                        } else {
+                            add(from, to, &mut moves);
+                        }
+                    }
+                },
+                PieceKind::Knight | PieceKind::King => {
+                    let steps = if piece.kind == PieceKind::Knight { &KNIGHT_STEPS } else { &KING_STEPS };
+                    for (dx, dy) in steps.iter() {
+                        if let Some(to) = Position::offset(from, *dx, *dy) {
+                            if self.piece_at(to).map_or(true, |other| other.colour != colour) {
+                                add(from, to, &mut moves);
+                            }
+                        }
+                    }
+                },
+                PieceKind::Bishop | PieceKind::Rook | PieceKind::Queen => {
+                    let directions: Vec<(i64, i64)> = match piece.kind {
+                        PieceKind::Bishop => BISHOP_DIRECTIONS.to_vec(),
+                        PieceKind::Rook => ROOK_DIRECTIONS.to_vec(),
+                        _ => BISHOP_DIRECTIONS.iter().chain(ROOK_DIRECTIONS.iter()).cloned().collect(),
+                    };
+                    for (dx, dy) in directions {
+                        let mut current = Position::offset(from, dx, dy);
+                        while let Some(to) = current {
+                            match self.piece_at(to) {
+                                None => add(from, to, &mut moves),
+                                Some(other) => {
+                                    if other.colour != colour {
+                                        add(from, to, &mut moves);
+                                    }
+                                    break;
+                                }
+                            }
+                            current = Position::offset(to, dx, dy);
+                        }
+                    }
+                },
+            }
+        }
+
+        // castling, the king may not castle out of, through or into check
+        let rank = colour.home_rank();
+        let king_from = Square{x: 4, y: rank};
+        let (king_side, queen_side) = match colour {
+            Colour::White => (self.castling.white_king_side, self.castling.white_queen_side),
+            Colour::Black => (self.castling.black_king_side, self.castling.black_queen_side),
+        };
+        let rook = Some(ChessPiece{colour, kind: PieceKind::Rook});
+        if self.piece_at(king_from) == Some(ChessPiece{colour, kind: PieceKind::King}) && !self.in_check(colour) {
+            let empty = |xs: &[usize]| xs.iter().all(|x| self.piece_at(Square{x: *x, y: rank}).is_none());
+            let safe = |xs: &[usize]| xs.iter().all(|x| !self.is_attacked(Square{x: *x, y: rank}, colour.opponent()));
+            if king_side && self.piece_at(Square{x: 7, y: rank}) == rook && empty(&[5, 6]) && safe(&[5, 6]) {
+                add(king_from, Square{x: 6, y: rank}, &mut moves);
+            }
+            if queen_side && self.piece_at(Square{x: 0, y: rank}) == rook && empty(&[1, 2, 3]) && safe(&[2, 3]) {
+                add(king_from, Square{x: 2, y: rank}, &mut moves);
+            }
+        }
+        moves
+    }
+
+    pub fn legal_moves(&self) -> Vec<ChessMove> {
+        let colour = self.to_move;
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|chess_move| !self.apply(chess_move).in_check(colour))
+            .collect()
+    }
+
+    pub fn is_legal(&self, chess_move: &ChessMove) -> bool {
+        self.legal_moves().contains(chess_move)
+    }
+
+    pub fn is_capture(&self, chess_move: &ChessMove) -> bool {
+        self.piece_at(chess_move.to).is_some() || self.is_en_passant(chess_move)
+    }
+
+    fn is_en_passant(&self, chess_move: &ChessMove) -> bool {
+        self.piece_at(chess_move.from).map_or(false, |piece| piece.kind == PieceKind::Pawn)
+            && self.en_passant == Some(chess_move.to)
+            && chess_move.from.x != chess_move.to.x
+    }
+
+    /// The position after making a move. The move is assumed to follow the movement rules.
+    pub fn apply(&self, chess_move: &ChessMove) -> Position {
+        let mut next = self.clone();
+        let (from, to) = (chess_move.from, chess_move.to);
+        let piece = match self.piece_at(from) {
+            Some(piece) => piece,
+            None => return next,
+        };
+        let capture = self.is_capture(chess_move);
+
+        if self.is_en_passant(chess_move) {
+            next.board[to.x][from.y] = None;
+        }
+        if piece.kind == PieceKind::King && (to.x as i64 - from.x as i64).abs() == 2 {
+            // castling also moves the rook to the other side of the king
+            let (rook_from, rook_to) = if to.x == 6 { (7, 5) } else { (0, 3) };
+            next.board[rook_to][from.y] = next.board[rook_from][from.y].take();
+        }
+        next.board[from.x][from.y] = None;
+        next.board[to.x][to.y] = Some(match chess_move.promotion {
+            Some(kind) if piece.kind == PieceKind::Pawn => ChessPiece{colour: piece.colour, kind},
+            _ => piece,
+        });
+
+        // moving the king or a rook, or having a rook captured, loses the right to castle
+        for square in [from, to].iter() {
+            match (square.x, square.y) {
+                (4, 0) => { next.castling.white_king_side = false; next.castling.white_queen_side = false; },
+                (4, 7) => { next.castling.black_king_side = false; next.castling.black_queen_side = false; },
+                (7, 0) => next.castling.white_king_side = false,
+                (0, 0) => next.castling.white_queen_side = false,
+                (7, 7) => next.castling.black_king_side = false,
+                (0, 7) => next.castling.black_queen_side = false,
+                _ => {},
+            }
+        }
+
+        next.en_passant = if piece.kind == PieceKind::Pawn && (to.y as i64 - from.y as i64).abs() == 2 {
+            Some(Square{x: from.x, y: (from.y + to.y) / 2})
+        } else {
+            None
+        };
+        next.halfmove_clock = if piece.kind == PieceKind::Pawn || capture { 0 } else { self.halfmove_clock + 1 };
+        if self.to_move == Colour::Black {
+            next.fullmove_number += 1;
+        }
+        next.to_move = self.to_move.opponent();
+        next
+    }
+
+    /*----------  FEN  ----------*/
+
+    pub fn from_fen(fen: &str) -> Result<Position, String> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err("FEN must have at least the placement, side to move, castling and en passant fields".into())
+        }
+
+        let mut board: Board = [[None; BOARD_SIZE]; BOARD_SIZE];
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != BOARD_SIZE {
+            return Err("FEN placement must have 8 ranks".into())
+        }
+        for (i, rank) in ranks.iter().enumerate() {
+            let y = BOARD_SIZE - 1 - i;
+            let mut x = 0;
+            for c in rank.chars() {
+                if let Some(empty) = c.to_digit(10) {
+                    x += empty as usize;
+                } else {
+                    let piece = piece_from_char(c).ok_or(format!("Unknown piece '{}' in FEN", c))?;
+                    if x >= BOARD_SIZE {
+                        return Err("FEN rank has more than 8 squares".into())
+                    }
+                    board[x][y] = Some(piece);
+                    x += 1;
+                }
+            }
+            if x != BOARD_SIZE {
+                return Err("FEN rank must have exactly 8 squares".into())
+            }
+        }
+
+        let to_move = match fields[1] {
+            "w" => Colour::White,
+            "b" => Colour::Black,
+            _ => return Err("FEN side to move must be w or b".into()),
+        };
+        let castling = Castling {
+            white_king_side: fields[2].contains('K'),
+            white_queen_side: fields[2].contains('Q'),
+            black_king_side: fields[2].contains('k'),
+            black_queen_side: fields[2].contains('q'),
+        };
+        let en_passant = match fields[3] {
+            "-" => None,
+            square => Some(parse_square(square).ok_or("FEN en passant square is not valid")?),
+        };
+        let halfmove_clock = fields.get(4).map_or(Ok(0), |n| n.parse()).map_err(|_| "FEN halfmove clock is not a number")?;
+        let fullmove_number = fields.get(5).map_or(Ok(1), |n| n.parse()).map_err(|_| "FEN fullmove number is not a number")?;
+
+        // the square must have just been passed over by a pawn of the side not to move, which is the pawn taken
+        if let Some(square) = en_passant {
+            let (rank, pawn_rank, start_rank) = match to_move {
+                Colour::Black => (2, 3, 1),
+                Colour::White => (5, 4, 6),
+            };
+            let pawn = ChessPiece{colour: to_move.opponent(), kind: PieceKind::Pawn};
+            if square.y != rank || board[square.x][rank].is_some() || board[square.x][start_rank].is_some()
+                || board[square.x][pawn_rank] != Some(pawn) {
+                return Err("FEN en passant square must be just behind a pawn of the side not to move that has moved two squares".into())
+            }
+        }
+
+        let position = Position{board, to_move, castling, en_passant, halfmove_clock, fullmove_number};
+        if position.king_square(Colour::White).is_none() || position.king_square(Colour::Black).is_none() {
+            return Err("Both sides must have a king".into())
+        }
+        if position.in_check(to_move.opponent()) {
+            return Err("The side that is not to move cannot be in check".into())
+        }
+        Ok(position)
+    }
+
+    pub fn to_fen(&self) -> String {
+        format!("{} {} {}", self.placement_fen(), self.halfmove_clock, self.fullmove_number)
+    }
+
+    /// The FEN without the move counters. Two positions with the same key are a repetition.
+    pub fn placement_fen(&self) -> String {
+        let ranks: Vec<String> = (0..BOARD_SIZE).rev().map(|y| {
+            let mut rank = String::new();
+            let mut empty = 0;
+            for x in 0..BOARD_SIZE {
+                match self.board[x][y] {
+                    Some(piece) => {
+                        if empty > 0 {
+                            rank.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        rank.push(piece_to_char(piece));
+                    },
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                rank.push_str(&empty.to_string());
+            }
+            rank
+        }).collect();
+
+        let mut castling = String::new();
+        if self.castling.white_king_side { castling.push('K') }
+        if self.castling.white_queen_side { castling.push('Q') }
+        if self.castling.black_king_side { castling.push('k') }
+        if self.castling.black_queen_side { castling.push('q') }
+        if castling.is_empty() { castling.push('-') }
+
+        // only record an en passant square if the capture is actually possible
+        let en_passant = match self.en_passant {
+            Some(square) if self.legal_moves().iter().any(|chess_move| self.is_en_passant(chess_move)) => square_name(square),
+            _ => "-".to_string(),
+        };
+
+        let to_move = match self.to_move { Colour::White => "w", Colour::Black => "b" };
+        format!("{} {} {} {}", ranks.join("/"), to_move, castling, en_passant)
+    }
+}
+
+/*========================================
+=            Helper functions            =
+========================================*/
+
+pub fn piece_letter(kind: PieceKind) -> char {
+    match kind {
+        PieceKind::Pawn => 'P',
+        PieceKind::Knight => 'N',
+        PieceKind::Bishop => 'B',
+        PieceKind::Rook => 'R',
+        PieceKind::Queen => 'Q',
+        PieceKind::King => 'K',
+    }
+}
+
+pub fn piece_to_char(piece: ChessPiece) -> char {
+    let letter = piece_letter(piece.kind);
+    match piece.colour {
+        Colour::White => letter,
+        Colour::Black => letter.to_ascii_lowercase(),
+    }
+}
+
+pub fn piece_from_char(c: char) -> Option<ChessPiece> {
+    let colour = if c.is_ascii_uppercase() { Colour::White } else { Colour::Black };
+    let kind = match c.to_ascii_uppercase() {
+        'P' => PieceKind::Pawn,
+        'N' => PieceKind::Knight,
+        'B' => PieceKind::Bishop,
+        'R' => PieceKind::Rook,
+        'Q' => PieceKind::Queen,
+        'K' => PieceKind::King,
+        _ => return None,
+    };
+    Some(ChessPiece{colour, kind})
+}
+
+pub fn file_name(x: usize) -> char {
+    (b'a' + x as u8) as char
+}
+
+pub fn rank_name(y: usize) -> char {
+    (b'1' + y as u8) as char
+}
+
+pub fn square_name(square: Square) -> String {
+    format!("{}{}", file_name(square.x), rank_name(square.y))
+}
+
+pub fn parse_square(name: &str) -> Option<Square> {
+    let bytes = name.as_bytes();
+    if bytes.len() != 2 || !(b'a'..=b'h').contains(&bytes[0]) || !(b'1'..=b'8').contains(&bytes[1]) {
+        return None
+    }
+    Some(Square{x: (bytes[0] - b'a') as usize, y: (bytes[1] - b'1') as usize})
+}
+
+/*=====  End of Helper functions  ======*/
@@ -0,0 +1,194 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+use hdk::holochain_persistence_api::cas::content::Address;
+use hdk::AGENT_ADDRESS;
+
+use crate::game_move::{Move, Outcome};
+use crate::game::Game;
+use super::{
+    MoveType,
+    position::{Colour, ChessMove, Position, BOARD_SIZE, START_FEN, file_name, piece_to_char},
+};
+
+pub const EMPTY_SPACE: char = ' ';
+
+/**
+ *
+ * A game normally starts from the standard position. It can instead start from any position
+ * given as a FEN string, optionally followed by opening moves in standard algebraic notation
+ * (e.g. ["e4", "e5", "Nf3"]). This is how a game is seeded from a PGN.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub fen: Option<String>,
+    pub opening: Vec<String>,
+}
+
+impl Settings {
+    pub fn start_position(&self) -> Result<Position, String> {
+        Position::from_fen(self.fen.as_ref().map_or(START_FEN, |fen| fen.as_str()))
+    }
+
+    pub fn is_valid(&self) -> Result<(), String> {
+        GameState::from_settings(self, &Address::from(""), &Address::from("")).map(|_| ())
+    }
+}
+
+/**
+ *
+ * For chess the state is built around the current Position. The moves are kept as made and in algebraic
+ * notation for exporting to PGN, along with every position reached so repetitions can be detected.
+ *
+ * The second player in the game plays white, the first black.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    FiftyMoveRule,
+    ThreefoldRepetition,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct GameState {
+    pub moves: Vec<Move>,
+    pub white: Address,
+    pub black: Address,
+    pub initial_fen: String,
+    pub position: Position,
+    pub san_moves: Vec<String>,
+    pub position_keys: Vec<String>,
+    pub termination: Option<Termination>,
+    pub outcome: Outcome,
+}
+
+impl GameState {
+    pub fn initial(game: &Game) -> Self {
+        GameState::from_settings(&game.settings(), &game.players[1], &game.players[0])
+            .unwrap_or_else(|_| GameState::from_settings(&Settings::default(), &game.players[1], &game.players[0]).unwrap())
+    }
+
+    fn from_settings(settings: &Settings, white: &Address, black: &Address) -> Result<Self, String> {
+        let position = settings.start_position()?;
+        let mut state = GameState {
+            moves: Vec::new(),
+            white: white.clone(),
+            black: black.clone(),
+            initial_fen: position.to_fen(),
+            position_keys: vec![position.placement_fen()],
+            position,
+            san_moves: Vec::new(),
+            termination: None,
+            outcome: Outcome::InProgress,
+        };
+        state.update_outcome();
+        for san in settings.opening.iter() {
+            if state.outcome.is_over() {
+                return Err(format!("The opening continues after the game has ended at {}", san))
+            }
+            let chess_move = state.position.parse_san(san)?;
+            state.play(&chess_move);
+        }
+        Ok(state)
+    }
+
+    pub fn player(&self, colour: Colour) -> &Address {
+        match colour {
+            Colour::White => &self.white,
+            Colour::Black => &self.black,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut disp = "\n".to_string();
+
+        if self.outcome.is_over() {
+            match &self.termination {
+                Some(termination) => disp.push_str(&format!("The game has ended ({:?}) \n", termination)),
+                None => disp.push_str("The game has ended \n"),
+            }
+        } else if self.player(self.position.to_move).to_string() == AGENT_ADDRESS.to_string() {
+            disp.push_str(&format!("It is your turn, you are playing {:?} \n", self.position.to_move));
+            if self.position.in_check(self.position.to_move) {
+                disp.push_str("You are in check! \n");
+            }
+        } else if self.moves.is_empty() {
+            disp.push_str("Your opponent moves first \n");
+        } else {
+            disp.push_str("It is your opponents turn \n");
+        }
+        disp.push('\n');
+
+        // white pieces are upper case, black pieces lower case
+        for y in (0..BOARD_SIZE).rev() {
+            disp.push_str(&format!("{} y={} |", y + 1, y));
+            for x in 0..BOARD_SIZE {
+                let c = self.position.board[x][y].map_or(EMPTY_SPACE, piece_to_char);
+                disp.push_str(&format!("{}|", c));
+            }
+            disp.push('\n');
+        }
+        disp.push_str("       ");
+        for x in 0..BOARD_SIZE {
+            disp.push_str(&format!("{} ", file_name(x)));
+        }
+        disp.push_str("\n     x=");
+        for x in 0..BOARD_SIZE {
+            disp.push_str(&format!("{} ", x));
+        }
+        disp.push('\n');
+        if !self.san_moves.is_empty() {
+            disp.push_str(&format!("\n{}\n", self.movetext()));
+        }
+        disp
+    }
+
+    pub fn evolve(&self, _game: Game, next_move: &Move, move_type: &MoveType) -> Self {
+        let mut state = self.clone();
+        state.moves.push(next_move.to_owned());
+        state.play(&move_type.to_chess_move());
+        state
+    }
+
+    /// Apply a legal move to the position and check whether it ended the game
+    fn play(&mut self, chess_move: &ChessMove) {
+        self.san_moves.push(self.position.san(chess_move));
+        self.position = self.position.apply(chess_move);
+        self.position_keys.push(self.position.placement_fen());
+        self.update_outcome();
+    }
+
+    fn update_outcome(&mut self) {
+        let to_move = self.position.to_move;
+        let repetitions = self.position_keys.iter()
+            .filter(|key| *key == self.position_keys.last().unwrap())
+            .count();
+
+        // the fifty-move rule and threefold repetition end the game automatically rather than needing a claim
+        let termination = if self.position.legal_moves().is_empty() {
+            if self.position.in_check(to_move) {
+                Some(Termination::Checkmate)
+            } else {
+                Some(Termination::Stalemate)
+            }
+        } else if self.position.halfmove_clock >= 100 {
+            Some(Termination::FiftyMoveRule)
+        } else if repetitions >= 3 {
+            Some(Termination::ThreefoldRepetition)
+        } else {
+            None
+        };
+
+        self.outcome = match termination {
+            Some(Termination::Checkmate) => Outcome::Winner(self.player(to_move.opponent()).clone()),
+            Some(_) => Outcome::Draw,
+            None => Outcome::InProgress,
+        };
+        self.termination = termination;
+    }
+}
@@ -0,0 +1,73 @@
+use hdk::holochain_persistence_api::{
+    cas::content::Address,
+};
+
+use crate::game::Game;
+use crate::game_type::is_players_turn;
+use super::{
+    Chess,
+    GameState,
+    moves::{PieceKind, Square},
+    MoveType,
+    position::{BOARD_SIZE, ChessMove},
+};
+
+
+/**
+ *
+ * A move is legal if the piece can make it and it doesn't leave the mover's own king in check.
+ * The errors try to say which of the rules a rejected move broke.
+ *
+ */
+
+
+impl MoveType {
+    pub fn is_valid(&self, game: Game, game_state: GameState, author: &Address) -> Result<(), String> {
+        match self {
+            MoveType::MovePiece{from, to, promotion} => {
+                is_players_turn::<Chess>(&game, &game_state, author)?;
+                from.is_in_bounds()?;
+                to.is_in_bounds()?;
+                let position = &game_state.position;
+                let piece = match position.piece_at(*from) {
+                    Some(piece) if piece.colour == position.to_move => piece,
+                    _ => return Err("You do not have a piece at that position.".into()),
+                };
+
+                let chess_move = ChessMove{from: *from, to: *to, promotion: *promotion};
+                if position.is_legal(&chess_move) {
+                    return Ok(())
+                }
+                let reaches_last_rank = piece.kind == PieceKind::Pawn && (to.y == 0 || to.y == BOARD_SIZE - 1);
+                match promotion {
+                    None if reaches_last_rank && position.is_legal(&ChessMove{promotion: Some(PieceKind::Queen), ..chess_move}) => {
+                        Err("A pawn reaching the last rank must be promoted, choose a Queen, Rook, Bishop or Knight.".into())
+                    },
+                    Some(_) if !reaches_last_rank => Err("Only a pawn reaching the last rank can be promoted.".into()),
+                    Some(PieceKind::Pawn) | Some(PieceKind::King) => Err("A pawn can only be promoted to a Queen, Rook, Bishop or Knight.".into()),
+                    _ if position.pseudo_legal_moves().contains(&chess_move) => Err("That move would leave your king in check.".into()),
+                    _ => Err(format!("A {:?} cannot move from ({}, {}) to ({}, {}).", piece.kind, from.x, from.y, to.x, to.y)),
+                }
+            }
+        }
+    }
+}
+
+
+/*========================================
+=            Helper functions            =
+========================================*/
+
+impl Square {
+    fn is_in_bounds(&self) -> Result<(), String> {
+        if self.x < BOARD_SIZE
+        && self.y < BOARD_SIZE // no need to check > 0 as usize is always positive
+        {
+            Ok(())
+        } else {
+            Err("Position is not in bounds".to_string())
+        }
+    }
+}
+
+/*=====  End of Helper functions  ======*/
@@ -0,0 +1,74 @@
+use hdk::{
+    error::ZomeApiError,
+    holochain_persistence_api::cas::content::Address,
+};
+
+/**
+ *
+ * Errors the game framework returns from its zome functions instead of panicking. They reach the caller
+ * as a `ZomeApiError::Internal` holding JSON with a machine-readable code, a message and the address
+ * the error is about, e.g. {"code":"MOVE_NOT_FOUND","message":"...","address":"Qm..."}
+ *
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameError {
+    /// A move linked from the game at the address could not be loaded
+    MoveNotFound(Address),
+    MalformedMove(Address),
+    GameNotFound(Address),
+    MalformedGame(Address),
+    /// The entry at the address is not of the type that was expected
+    WrongEntryType(Address),
+    /// The agent at the address is not playing in the game
+    NotAPlayer(Address),
+    /// The game at the address was created from a proposal whose author hasn't confirmed it
+    NotConfirmed(Address),
+}
+
+impl GameError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            GameError::MoveNotFound(_) => "MOVE_NOT_FOUND",
+            GameError::MalformedMove(_) => "MALFORMED_MOVE",
+            GameError::GameNotFound(_) => "GAME_NOT_FOUND",
+            GameError::MalformedGame(_) => "MALFORMED_GAME",
+            GameError::WrongEntryType(_) => "WRONG_ENTRY_TYPE",
+            GameError::NotAPlayer(_) => "NOT_A_PLAYER",
+            GameError::NotConfirmed(_) => "NOT_CONFIRMED",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            GameError::MoveNotFound(_) => "Could not load a move of the game".into(),
+            GameError::MalformedMove(_) => "The entry could not be read as a move".into(),
+            GameError::GameNotFound(_) => "Could not find the game".into(),
+            GameError::MalformedGame(_) => "The entry could not be read as a game".into(),
+            GameError::WrongEntryType(_) => "The entry is of another type than expected".into(),
+            GameError::NotAPlayer(_) => "The agent is not a player in this game".into(),
+            GameError::NotConfirmed(_) => "The author of the proposal has not confirmed this game".into(),
+        }
+    }
+
+    pub fn address(&self) -> &Address {
+        match self {
+            GameError::MoveNotFound(address)
+            | GameError::MalformedMove(address)
+            | GameError::GameNotFound(address)
+            | GameError::MalformedGame(address)
+            | GameError::WrongEntryType(address)
+            | GameError::NotAPlayer(address)
+            | GameError::NotConfirmed(address) => address,
+        }
+    }
+}
+
+impl From<GameError> for ZomeApiError {
+    fn from(error: GameError) -> Self {
+        ZomeApiError::Internal(serde_json::json!({
+            "code": error.code(),
+            "message": error.message(),
+            "address": error.address().to_string(),
+        }).to_string())
+    }
+}
@@ -1,13 +1,14 @@
 use std::convert::TryFrom;
+use serde::de::DeserializeOwned;
 use hdk::{
-    utils,
+    AGENT_ADDRESS,
     entry_definition::ValidatingEntryType,
     error::{ZomeApiResult, ZomeApiError},
     holochain_persistence_api::{
         cas::content::{AddressableContent, Address},
     },
     holochain_json_api::{
-        error::JsonError, json::JsonString,
+        error::JsonError, json::{JsonString, default_to_json},
     },
     holochain_core_types::{
         dna::entry_types::Sharing,
@@ -17,62 +18,529 @@ use hdk::{
     }
 };
 
-use crate::game_move::Move;
-use crate::GameState;
+use crate::error::GameError;
+use crate::chess::{self, Chess};
+use crate::go::{self, Go};
+use crate::game_move::{Move, MoveInput, MoveType, MetaMove, Outcome, Secret};
+use crate::game_type::{GameType, GameRules, MetaState, describe_moves};
+use crate::matchmaking;
+use crate::snapshot::{commit_snapshot_if_due, get_snapshots, reduce_from_snapshots};
+use crate::time_control::{TimeControl, civil_date, unix_seconds};
 
 #[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
 pub struct Game {
-    pub player_1: Address,
-    pub player_2: Address,
+    pub game_type: GameType,
+    /// Options for the game type such as the board size. Null for the defaults.
+    #[serde(default)]
+    pub settings: serde_json::Value,
+    /// The players in turn order. The agent that created the game is first.
+    /// In a team game these are the first member of each team.
+    pub players: Vec<Address>,
+    /// Empty unless the players play in teams
+    #[serde(default)]
+    pub teams: Vec<Team>,
+    #[serde(default)]
+    pub time_control: Option<TimeControl>,
+    /// Whether the game was proposed as a rated game
+    #[serde(default)]
+    pub rated: bool,
+    /// The proposal the game was created from. Moves can only be made once the proposer has confirmed it.
+    #[serde(default)]
+    pub proposal: Option<Address>,
     pub created_at: u32,
 }
 
+/**
+ *
+ * In a team game every team takes a single seat in `Game::players`, held by its first member.
+ * Any member may make the team's move, or with `TeamTurns::Rotation` the members take the team's
+ * moves in turn. Either way the framework hands the move to the game rules as if the team's seat made it.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct Team {
+    pub name: String,
+    pub members: Vec<Address>,
+    #[serde(default)]
+    pub turns: TeamTurns,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum TeamTurns {
+    AnyMember,
+    Rotation,
+}
+
+impl Default for TeamTurns {
+    fn default() -> Self {
+        TeamTurns::AnyMember
+    }
+}
+
+impl Team {
+    /// The member who must make the team's next move, if the members take turns
+    pub fn member_to_move(&self, moves_made: usize) -> Option<&Address> {
+        match self.turns {
+            TeamTurns::AnyMember => None,
+            TeamTurns::Rotation => self.members.get(moves_made % self.members.len().max(1)),
+        }
+    }
+}
+
+/// How a game ended for one side, either a team or a player in a game without teams
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct TeamOutcome {
+    pub team: String,
+    pub members: Vec<Address>,
+    pub result: TeamResult,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum TeamResult {
+    InProgress,
+    Won,
+    Lost,
+    Draw,
+    Abandoned,
+}
+
+/// The most moves `get_moves_page` returns at once
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/**
+ *
+ * Link validation only lets one move follow each game or move, but agents that can't see each other
+ * can still both make a move on the same base. `get_moves` follows the canonical branch of such a fork,
+ * the move with the earliest timestamp and then the lowest address, so every agent sees the same game.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct Fork {
+    /// The game or move that was followed by more than one move
+    pub base: Address,
+    pub canonical: Address,
+    pub discarded: Vec<Address>,
+}
+
+/// Evidence that a player made more than one move on the same base, which their opponent
+/// can hand in with the ClaimForfeit move to win the game
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct DoubleMove {
+    pub author: Address,
+    pub base: Address,
+    pub moves: Vec<Move>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct GameIntegrity {
+    pub forks: Vec<Fork>,
+    pub double_moves: Vec<DoubleMove>,
+}
+
+impl Game {
+    pub fn try_settings<T: DeserializeOwned + Default>(&self) -> Result<T, String> {
+        if self.settings.is_null() {
+            Ok(T::default())
+        } else {
+            serde_json::from_value(self.settings.clone())
+                .map_err(|e| format!("Settings are not valid for this game: {}", e))
+        }
+    }
+
+    /// The settings for the game type. Invalid settings are rejected when the game is created.
+    pub fn settings<T: DeserializeOwned + Default>(&self) -> T {
+        self.try_settings().unwrap_or_default()
+    }
+
+    /// The (year, month, day) the game was created on, reading `created_at` as a unix timestamp
+    pub fn created_date(&self) -> (i64, i64, i64) {
+        civil_date(i64::from(self.created_at))
+    }
+
+    pub fn is_player(&self, agent: &Address) -> bool {
+        self.players.contains(agent) || self.team_of(agent).is_some()
+    }
+
+    pub fn team_of(&self, agent: &Address) -> Option<&Team> {
+        self.teams.iter().find(|team| team.members.contains(agent))
+    }
+
+    /// The player an agent moves as. Members of a team all move as the team's seat.
+    pub fn seat_of(&self, agent: &Address) -> Address {
+        match self.team_of(agent) {
+            Some(team) => team.members[0].clone(),
+            None => agent.clone(),
+        }
+    }
+
+    /// The move as the game rules see it, made by the seat of the agent that authored it
+    pub fn seated(&self, next_move: &Move) -> Move {
+        Move {
+            author: self.seat_of(&next_move.author),
+            ..next_move.clone()
+        }
+    }
+
+    /// The teams of the game. Without teams each player is a side of their own.
+    pub fn sides(&self) -> Vec<Team> {
+        if !self.teams.is_empty() {
+            return self.teams.clone()
+        }
+        self.players.iter().enumerate().map(|(i, player)| Team {
+            name: format!("Player {}", i + 1),
+            members: vec![player.clone()],
+            turns: TeamTurns::AnyMember,
+        }).collect()
+    }
+
+    /// How the game ended for each side
+    pub fn team_outcomes(&self, outcome: &Outcome) -> Vec<TeamOutcome> {
+        self.sides().into_iter().map(|team| {
+            let result = match outcome {
+                Outcome::InProgress => TeamResult::InProgress,
+                Outcome::Winner(winner) if winner == &team.members[0] => TeamResult::Won,
+                Outcome::Winner(_) => TeamResult::Lost,
+                Outcome::Draw => TeamResult::Draw,
+                Outcome::Abandoned => TeamResult::Abandoned,
+            };
+            TeamOutcome{team: team.name, members: team.members, result}
+        }).collect()
+    }
+
+    /// The position of the agent in the turn order, if they are playing
+    pub fn player_index(&self, agent: &Address) -> Option<usize> {
+        self.players.iter().position(|player| player == agent)
+    }
+
+    /// By convention the second player makes the first move thus accepting the invitation to play
+    pub fn first_player(&self) -> Address {
+        self.players[1 % self.players.len()].clone()
+    }
+
+    /// The player who makes the first move under the rules of the game, e.g. the first of the players in a chess game
+    /// started from a position with black to move. Games that don't say follow `first_player`.
+    pub fn first_mover(&self) -> Address {
+        dispatch!(self.game_type, G => G::to_move(self, &G::initial(self))).unwrap_or_else(|| self.first_player())
+    }
+
+    /// The player whose turn follows the agent's, going round the players in order
+    pub fn next_player(&self, agent: &Address) -> Address {
+        match self.player_index(agent) {
+            Some(index) => self.players[(index + 1) % self.players.len()].clone(),
+            None => self.first_player(),
+        }
+    }
+}
+
 /*=====================================
 =            DHT Functions            =
 =====================================*/
 
-/// Traverse the linked list rooted at a game to find all the moves
-pub fn get_moves(game_address: &Address) -> ZomeApiResult<Vec<Move>> {
-    match hdk::get_links(game_address, LinkMatch::Any, LinkMatch::Any)?.addresses().into_iter().next() {
-        Some(first_move) => {
-            let mut move_addresses = vec![first_move];
-            let mut more = true;
-            while more {
-                more = match hdk::get_links(move_addresses.last().unwrap(), LinkMatch::Any, LinkMatch::Any)?.addresses().into_iter().next() {
-                    Some(addr) => {
-                        move_addresses.push(addr.clone());
-                        true
-                    },
-                    None => {
-                        false
-                    },
+/// Commit a new game against the opponents, the agent creating it is the first player
+pub fn create_game(opponents: Vec<Address>, timestamp: u32, game_type: GameType, settings: serde_json::Value, time_control: Option<TimeControl>) -> ZomeApiResult<Address> {
+    let mut players: Vec<Address> = vec![AGENT_ADDRESS.to_string().into()];
+    players.extend(opponents);
+    let new_game = Game {
+        game_type,
+        settings,
+        players,
+        teams: Vec::new(),
+        time_control,
+        rated: false,
+        proposal: None,
+        created_at: timestamp,
+    };
+    let game_entry = Entry::App(
+        "game".into(),
+        new_game.into(),
+    );
+    hdk::commit_entry(&game_entry)
+}
+
+/// Commit a new game between teams. The teams take turns in the order given.
+pub fn create_team_game(teams: Vec<Team>, timestamp: u32, game_type: GameType, settings: serde_json::Value, time_control: Option<TimeControl>) -> ZomeApiResult<Address> {
+    let new_game = Game {
+        game_type,
+        settings,
+        players: teams.iter().filter_map(|team| team.members.first().cloned()).collect(),
+        teams,
+        time_control,
+        rated: false,
+        proposal: None,
+        created_at: timestamp,
+    };
+    let game_entry = Entry::App(
+        "game".into(),
+        new_game.into(),
+    );
+    hdk::commit_entry(&game_entry)
+}
+
+/// Commit a move by this agent, after bringing their chain up to date with the moves and snapshots of the game
+pub fn make_move(new_move: MoveInput) -> ZomeApiResult<()> {
+    let game = get_game(&new_move.game)?;
+    if !game.is_player(&AGENT_ADDRESS) {
+        return Err(GameError::NotAPlayer(AGENT_ADDRESS.to_string().into()).into())
+    }
+    // validation refuses these moves too, this only gives a clearer error. The Confirm itself is made straight
+    // after the confirmation link, which may not be seen yet
+    let confirming = new_move.move_type == MoveType::Meta(MetaMove::Confirm);
+    if let (Some(proposal), false) = (&game.proposal, confirming) {
+        if matchmaking::confirmed_game(proposal)?.as_ref() != Some(&new_move.game) {
+            return Err(GameError::NotConfirmed(new_move.game.clone()).into())
+        }
+    }
+
+    // get all the moves from the DHT by following the hash chain
+    let published_moves = get_moves(&new_move.game)?;
+
+    // get all moves in this agents local chain
+    let chain_moves = hdk::query("move".into(), 0, 0)?;
+
+    // Update this agents local chain to match the game state
+    let base_address = match published_moves.clone().last() {
+        Some(last_move) => { // add any moves NOT found in the DHT to this agents local chain
+            for _move in &published_moves {
+                let move_entry = Entry::App("move".into(), _move.into());
+                if !chain_moves.contains(&move_entry.address()) {
+                    hdk::commit_entry(&move_entry)?;
                 }
             }
-            let moves: Vec<Move> = move_addresses.iter().map(|addr| {
-                let move_entry = hdk::get_entry(addr).unwrap().unwrap();
-                if let Entry::App(_, move_struct) = move_entry {
-                    Move::try_from(move_struct).expect("Entry at address is type other than Move")
-                } else {
-                    panic!("Not an app entry!")
+            // and any snapshots, so validating the new move can start from them
+            let chain_snapshots = hdk::query("snapshot".into(), 0, 0)?;
+            for snapshot in get_snapshots(&new_move.game)? {
+                let snapshot_entry = Entry::App("snapshot".into(), snapshot.into());
+                if !chain_snapshots.contains(&snapshot_entry.address()) {
+                    hdk::commit_entry(&snapshot_entry)?;
                 }
-            }).collect();
-            Ok(moves)
-        },
+            }
+            Entry::App("move".into(), last_move.into()).address()
+
+        }
+        None => { // no moves have been made so commit the Game to local chain
+            let game_entry = Entry::App("game".into(), game.clone().into());
+            hdk::commit_entry(&game_entry)?
+        }
+    };
+
+    let game_address = new_move.game.clone();
+    let new_move = Move {
+        game: new_move.game,
+        author: AGENT_ADDRESS.to_string().into(),
+        move_type: new_move.move_type,
+        previous_move: base_address.clone(),
+        timestamp: new_move.timestamp,
+    };
+    let move_entry = Entry::App(
+        "move".into(),
+        new_move.clone().into(),
+    );
+    let move_address = hdk::commit_entry(&move_entry)?;
+
+    // links are tagged with the index of the move in the game
+    let index = published_moves.len().to_string();
+    match published_moves.last() {
+        Some(_) => {
+            // base is a move
+            hdk::link_entries(&base_address, &move_address, "move->move", &index)?;
+        }
         None => {
-            Ok(Vec::new())
+            // base is a game
+            hdk::link_entries(&base_address, &move_address, "game->move", &index)?;
+        }
+    }
+    // every move is also linked from the game so they can all be loaded at once
+    hdk::link_entries(&game_address, &move_address, "game->moves", &index)?;
+
+    let mut moves = published_moves;
+    moves.push(new_move);
+    dispatch!(game.game_type, G => {
+        commit_snapshot_if_due::<G>(&game_address, &game, &moves)
+    })?;
+
+    Ok(())
+}
+
+/// Load all the moves of a game at once and put them in order, following the canonical branch of any fork
+pub fn get_moves(game_address: &Address) -> ZomeApiResult<Vec<Move>> {
+    let moves = load_moves(game_address, LinkMatch::Any)?;
+    Ok(chain_moves(game_address, &moves).0)
+}
+
+/// The forks in the moves of a game and any evidence of a player moving twice
+pub fn get_game_integrity(game_address: &Address) -> ZomeApiResult<GameIntegrity> {
+    let moves = load_moves(game_address, LinkMatch::Any)?;
+    Ok(chain_moves(game_address, &moves).1)
+}
+
+/// The moves of a game starting at index `from`, at most `limit` of them and never more than MAX_PAGE_SIZE
+pub fn get_moves_page(game_address: &Address, from: u32, limit: u32) -> ZomeApiResult<Vec<Move>> {
+    let indices: Vec<String> = (from..from.saturating_add(limit.min(MAX_PAGE_SIZE))).map(|index| index.to_string()).collect();
+    if indices.is_empty() {
+        return Ok(Vec::new())
+    }
+    let moves = load_moves(game_address, LinkMatch::Regex(format!("^({})$", indices.join("|")).as_str()))?;
+    // the page follows on from a move that isn't on it, or the game for the first page
+    let addresses: Vec<Address> = moves.iter().map(move_address).collect();
+    let base = moves.iter()
+        .filter(|game_move| !addresses.contains(&game_move.previous_move))
+        .min_by_key(|game_move| (game_move.timestamp, move_address(game_move).to_string()))
+        .map(|game_move| game_move.previous_move.clone());
+    match base {
+        Some(base) => Ok(chain_moves(&base, &moves).0),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Every move is linked from its game with its index as the tag, so one query loads them all
+fn load_moves(game_address: &Address, index: LinkMatch<&str>) -> ZomeApiResult<Vec<Move>> {
+    hdk::get_links_and_load(game_address, LinkMatch::Exactly("game->moves"), index)?
+        .into_iter()
+        .map(|entry| {
+            let entry = entry.map_err(|_| GameError::MoveNotFound(game_address.clone()))?;
+            let address = entry.address();
+            match entry {
+                Entry::App(entry_type, move_struct) if entry_type.to_string() == "move" => {
+                    Move::try_from(move_struct).map_err(|_| GameError::MalformedMove(address).into())
+                },
+                _ => Err(GameError::WrongEntryType(address).into()),
+            }
+        })
+        .collect()
+}
+
+/// Put the moves in order by following each move's previous move from the base. Where more than one
+/// move follows the same base the earliest is taken, ties broken by the lowest address.
+fn chain_moves(base: &Address, moves: &[Move]) -> (Vec<Move>, GameIntegrity) {
+    let mut chain = Vec::new();
+    let mut integrity = GameIntegrity{forks: Vec::new(), double_moves: Vec::new()};
+    let mut base = base.clone();
+    loop {
+        let mut successors: Vec<(Address, Move)> = moves.iter()
+            .filter(|game_move| game_move.previous_move == base)
+            .map(|game_move| (move_address(game_move), game_move.clone()))
+            .collect();
+        successors.sort_by_key(|(addr, next_move)| (next_move.timestamp, addr.to_string()));
+        successors.dedup_by(|(a, _), (b, _)| a == b);
+
+        let (canonical, next_move) = match successors.first() {
+            Some(successor) => successor.clone(),
+            None => break,
+        };
+        if successors.len() > 1 {
+            integrity.forks.push(Fork {
+                base: base.clone(),
+                canonical: canonical.clone(),
+                discarded: successors[1..].iter().map(|(addr, _)| addr.clone()).collect(),
+            });
+            let mut authors: Vec<Address> = successors.iter().map(|(_, successor)| successor.author.clone()).collect();
+            authors.sort_by_key(|author| author.to_string());
+            authors.dedup();
+            for author in authors {
+                let by_author: Vec<Move> = successors.iter()
+                    .filter(|(_, successor)| successor.author == author)
+                    .map(|(_, successor)| successor.clone())
+                    .collect();
+                if by_author.len() > 1 {
+                    integrity.double_moves.push(DoubleMove{author, base: base.clone(), moves: by_author});
+                }
+            }
         }
+        chain.push(next_move);
+        base = canonical;
     }
+    (chain, integrity)
 }
 
-pub fn get_state(game_address: &Address) -> ZomeApiResult<GameState> {
+fn move_address(game_move: &Move) -> Address {
+    Entry::App("move".into(), game_move.clone().into()).address()
+}
+
+/// The state of the game after all of its moves, reduced from the latest snapshot
+pub fn load_state<G: GameRules>(game_address: &Address, game: &Game) -> ZomeApiResult<MetaState<G::State>> {
     let moves = get_moves(game_address)?;
+    let snapshots = get_snapshots(game_address)?;
+    reduce_from_snapshots::<G>(game, &snapshots, &moves).map_err(ZomeApiError::Internal)
+}
+
+/// The state of the game as JSON. Its shape depends on the type of game being played.
+pub fn get_state(game_address: &Address) -> ZomeApiResult<JsonString> {
     let game = get_game(game_address)?;
-    let new_state = moves.iter().fold(GameState::initial(), |state, new_move| state.evolve(game.clone(), new_move));
-    Ok(new_state)
+    dispatch!(game.game_type, G => {
+        let state = load_state::<G>(game_address, &game)?;
+        Ok(default_to_json(state))
+    })
+}
+
+pub fn render_state(game_address: &Address) -> ZomeApiResult<String> {
+    let game = get_game(game_address)?;
+    dispatch!(game.game_type, G => {
+        let state = load_state::<G>(game_address, &game)?;
+        Ok(state.render::<G>(&game))
+    })
+}
+
+/// How the game ended, or InProgress if it is still being played
+pub fn get_result(game_address: &Address) -> ZomeApiResult<Outcome> {
+    let game = get_game(game_address)?;
+    dispatch!(game.game_type, G => {
+        let state = load_state::<G>(game_address, &game)?;
+        Ok(state.outcome)
+    })
+}
+
+pub fn get_valid_moves(game_address: &Address) -> ZomeApiResult<JsonString> {
+    let game = get_game(game_address)?;
+    dispatch!(game.game_type, G => {
+        Ok(default_to_json(describe_moves::<G>()))
+    })
+}
+
+/// The commitment to seal a move with in the current round of a simultaneous game
+pub fn hash_move(game_address: &Address, move_type: serde_json::Value, salt: String) -> ZomeApiResult<Address> {
+    let game = get_game(game_address)?;
+    dispatch!(game.game_type, G => {
+        let state = load_state::<G>(game_address, &game)?;
+        let seat = game.seat_of(&AGENT_ADDRESS.to_string().into());
+        Ok(Secret::sealed_move(&seat, state.round, &move_type, &salt).commitment())
+    })
+}
+
+/// How the game ended for each team, or for each player if the game isn't played in teams
+pub fn get_team_results(game_address: &Address) -> ZomeApiResult<Vec<TeamOutcome>> {
+    let outcome = get_result(game_address)?;
+    let game = get_game(game_address)?;
+    Ok(game.team_outcomes(&outcome))
+}
+
+/// Export a chess game as PGN
+pub fn get_pgn(game_address: &Address) -> ZomeApiResult<String> {
+    let game = get_game(game_address)?;
+    if game.game_type != GameType::Chess {
+        return Err(ZomeApiError::Internal("Only chess games can be exported as PGN".into()))
+    }
+    let state = load_state::<Chess>(game_address, &game)?;
+    Ok(chess::pgn::to_pgn(&game, &state.game_state, &state.outcome))
+}
+
+/// Export a go game as SGF
+pub fn get_sgf(game_address: &Address) -> ZomeApiResult<String> {
+    let game = get_game(game_address)?;
+    if game.game_type != GameType::Go {
+        return Err(ZomeApiError::Internal("Only go games can be exported as SGF".into()))
+    }
+    let state = load_state::<Go>(game_address, &game)?;
+    Ok(go::sgf::to_sgf(&game, &state.game_state, &state.outcome))
 }
 
 pub fn get_game(game_address: &Address) -> ZomeApiResult<Game> {
-    utils::get_as_type(game_address.to_owned())
+    match hdk::get_entry(game_address)? {
+        Some(Entry::App(entry_type, game_struct)) if entry_type.to_string() == "game" => {
+            Game::try_from(game_struct).map_err(|_| GameError::MalformedGame(game_address.clone()).into())
+        },
+        Some(_) => Err(GameError::WrongEntryType(game_address.clone()).into()),
+        None => Err(GameError::GameNotFound(game_address.clone()).into()),
+    }
 }
 
 /*=====  End of DHT Functions  ======*/
@@ -87,26 +555,26 @@ pub fn get_game_local_chain(local_chain: Vec<Entry>, game_address: &Address) ->
     local_chain
         .iter()
         .filter(|entry| {
-            entry.address() == game_address.to_owned()
+            &entry.address() == game_address
         })
         .filter_map(|entry| {
             if let Entry::App(_, entry_data) = entry {
-                Some(Game::try_from(entry_data.clone()).unwrap())
+                Some(Game::try_from(entry_data.clone()).map_err(|_| GameError::MalformedGame(game_address.clone()).into()))
             } else {
                 None
             }
         })
         .next()
-        .ok_or(ZomeApiError::HashNotFound)
+        .unwrap_or_else(|| Err(GameError::GameNotFound(game_address.clone()).into()))
 }
 
 pub fn get_moves_local_chain(local_chain: Vec<Entry>, game_address: &Address) -> ZomeApiResult<Vec<Move>> {
-    Ok(local_chain
+    let moves = local_chain
         .iter()
         .filter_map(|entry| {
             if let Entry::App(entry_type, entry_data) = entry {
                 if entry_type.to_string() == "move" {
-                    Some(Move::try_from(entry_data.clone()).unwrap())
+                    Some(Move::try_from(entry_data.clone()).map_err(|_| GameError::MalformedMove(entry.address())))
                 } else {
                     None
                 }
@@ -114,25 +582,26 @@ pub fn get_moves_local_chain(local_chain: Vec<Entry>, game_address: &Address) ->
                 None
             }
         })
+        .collect::<Result<Vec<Move>, GameError>>()?;
+    Ok(moves
+        .into_iter()
         .filter(|game_move| {
-            game_move.game == game_address.to_owned()
+            &game_move.game == game_address
         })
         .rev()
         .collect())
 }
 
-pub fn get_state_local_chain(local_chain: Vec<Entry>, game_address: &Address) -> ZomeApiResult<GameState> {
-    let moves = get_moves_local_chain(local_chain.clone(), game_address)?;
-    let game = get_game_local_chain(local_chain, game_address)?;
-    let new_state = moves.iter().fold(GameState::initial(), move |state, new_move| state.evolve(game.clone(), new_move));
-    Ok(new_state)
-}
-
 
 /*=====  End of Local chain functions  ======*/
 
 
 
+/// Agent addresses are the encoding of their public key, 63 letters and digits starting with Hc
+fn is_agent_address(address: &Address) -> bool {
+    let address = address.to_string();
+    address.starts_with("Hc") && address.len() == 63 && address.chars().all(|c| c.is_ascii_alphanumeric())
+}
 
 pub fn definition() -> ValidatingEntryType {
     entry!(
@@ -145,12 +614,50 @@ pub fn definition() -> ValidatingEntryType {
 
         validation: | validation_data: hdk::EntryValidationData<Game>| {
             match validation_data {
-                EntryValidationData::Create{entry, validation_data: _} => {
+                EntryValidationData::Create{entry, validation_data} => {
                     let game = entry as Game;
-                    if game.player_1 == game.player_2 {
-                        return Err("Player 1 and Player 2 must be different agents.".into())
+                    if !validation_data.sources().iter().any(|source| game.is_player(source)) {
+                        return Err("A game can only be created by one of its players.".into())
+                    }
+                    let mut agents = game.players.iter().chain(game.teams.iter().flat_map(|team| team.members.iter()));
+                    if let Some(agent) = agents.find(|agent| !is_agent_address(agent)) {
+                        return Err(format!("{} is not the address of an agent.", agent))
+                    }
+                    let header_time = unix_seconds(&validation_data.package.chain_header.timestamp().to_string())
+                        .ok_or("Could not read the time of the game's header")?;
+                    if i64::from(game.created_at) > header_time {
+                        return Err("A game can't be created later than the time it was committed.".into())
+                    }
+                    if game.players.iter().enumerate().any(|(i, player)| game.players[i + 1..].contains(player)) {
+                        return Err("Every player must be a different agent.".into())
+                    }
+                    if !game.teams.is_empty() {
+                        if game.teams.iter().any(|team| team.members.is_empty()) {
+                            return Err("Every team must have at least one member.".into())
+                        }
+                        let members: Vec<&Address> = game.teams.iter().flat_map(|team| team.members.iter()).collect();
+                        if members.iter().enumerate().any(|(i, member)| members[i + 1..].contains(member)) {
+                            return Err("An agent can only be a member of one team.".into())
+                        }
+                        let seats: Vec<Address> = game.teams.iter().map(|team| team.members[0].clone()).collect();
+                        if seats != game.players {
+                            return Err("The players of a team game must be the first member of each team.".into())
+                        }
+                    }
+                    if let Some(time_control) = &game.time_control {
+                        // a player who runs out of time loses to their opponent, which needs there to be only one
+                        if game.players.len() > 2 {
+                            return Err("A time control can only be used in two player games.".into())
+                        }
+                        time_control.is_valid()?;
                     }
-                    Ok(())
+                    dispatch!(game.game_type, G => {
+                        let (min, max) = G::player_count();
+                        if game.players.len() < min || game.players.len() > max {
+                            return Err(format!("This game is played by {} to {} players.", min, max))
+                        }
+                        G::validate_game(&game)
+                    })
                 },
                 _ => {
                     Err("Cannot modify or delete a game".into())
@@ -1,22 +1,220 @@
+use std::convert::TryFrom;
 use hdk::{
     entry_definition::ValidatingEntryType,
     holochain_persistence_api::{
-        cas::content::{Address},
+        cas::content::{Address, AddressableContent},
     },
     holochain_json_api::{
         error::JsonError, json::JsonString,
     },
     holochain_core_types::{
         dna::entry_types::Sharing,
-        validation::EntryValidationData,
+        validation::{EntryValidationData, LinkValidationData, ValidationData},
         entry::Entry,
     }
 };
 
-use crate::MoveType;
-use crate::game::{get_game_local_chain, get_state_local_chain};
+use crate::game::{Game, DoubleMove, get_game_local_chain, get_moves_local_chain};
+use crate::game_type::MetaState;
+use crate::snapshot::{reduce_from_snapshots, get_snapshots_local_chain};
+use crate::time_control::unix_seconds;
 
 
+/**
+ *
+ * Moves that every game supports. These are handled by the framework and never reach the GameRules
+ * of the game being played. Most are written as plain strings, e.g. "Resign".
+ *
+ * Every move is public, so games with hidden information use Commit and Reveal. A player first commits
+ * to secret data by publishing only its hash (see `Secret::commitment`) and later reveals the data and
+ * salt, which must hash to the commitment. The game is not over until every commitment is revealed.
+ *
+ * Dice work the same way. Every player commits to a random seed with RandomnessRequest and once all of
+ * them have done so they reveal their seeds with RandomnessReveal. The seeds are combined into a
+ * `Randomness` that no single player could have chosen.
+ *
+ * In games where every player moves at once each round, a player seals their move for the round with
+ * SealMove (see `Secret::sealed_move`) and reveals it with RevealMove once every player has sealed one.
+ *
+ * A draw offered with OfferDraw is only agreed once every other player has answered it with AcceptDraw.
+ *
+ * In a game with a time control a player wins with ClaimTimeout once their opponent's clock has run out.
+ * Time controls are limited to two player games, since with more there is no single opponent to win.
+ *
+ * A player who made two moves on the same base forfeits the game once their opponent hands in the
+ * moves with ClaimForfeit, see `get_game_integrity`.
+ *
+ * A game created in response to a proposal starts with a Confirm by the author of the proposal, which
+ * `confirm_response` makes once it has confirmed the game. No other move can be made before it.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum MetaMove {
+    Abandon,
+    Resign,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    Commit {
+        key: String,
+        commitment: Address,
+    },
+    Reveal {
+        key: String,
+        data: serde_json::Value,
+        salt: String,
+    },
+    RandomnessRequest {
+        key: String,
+        commitment: Address,
+    },
+    RandomnessReveal {
+        key: String,
+        seed: String,
+        salt: String,
+    },
+    SealMove {
+        commitment: Address,
+    },
+    RevealMove {
+        move_type: serde_json::Value,
+        salt: String,
+    },
+    ClaimTimeout,
+    ClaimForfeit {
+        evidence: DoubleMove,
+    },
+    Confirm,
+}
+
+impl MetaMove {
+    pub fn describe() -> Vec<MetaMove> {
+        vec![
+            MetaMove::Abandon,
+            MetaMove::Resign,
+            MetaMove::OfferDraw,
+            MetaMove::AcceptDraw,
+            MetaMove::DeclineDraw,
+            MetaMove::Commit{key: "secret".into(), commitment: "<address from hash_secret>".into()},
+            MetaMove::Reveal{key: "secret".into(), data: serde_json::Value::Null, salt: "<random salt>".into()},
+            MetaMove::RandomnessRequest{key: "roll".into(), commitment: "<address from hash_secret with the seed as data>".into()},
+            MetaMove::RandomnessReveal{key: "roll".into(), seed: "<random seed>".into(), salt: "<random salt>".into()},
+            MetaMove::SealMove{commitment: "<address from hash_move>".into()},
+            MetaMove::RevealMove{move_type: serde_json::Value::Null, salt: "<random salt>".into()},
+            MetaMove::ClaimTimeout,
+            MetaMove::ClaimForfeit{evidence: DoubleMove{author: "<agent who moved twice>".into(), base: "<address>".into(), moves: Vec::new()}},
+        ]
+    }
+}
+
+/// The data a player commits to. The key and a random salt are hashed with the data so the
+/// opponent can't guess it from the commitment or swap it with another revealed secret.
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct Secret {
+    pub key: String,
+    pub data: serde_json::Value,
+    pub salt: String,
+}
+
+impl Secret {
+    pub fn commitment(&self) -> Address {
+        Entry::App("secret".into(), self.clone().into()).address()
+    }
+}
+
+impl Secret {
+    /// The secret a seed for shared randomness is committed as
+    pub fn seed(key: &str, seed: &str, salt: &str) -> Self {
+        Secret {
+            key: key.to_string(),
+            data: serde_json::Value::String(seed.to_string()),
+            salt: salt.to_string(),
+        }
+    }
+
+    /// The secret a move in a simultaneous round is sealed as. The author and round are part of the key
+    /// so a player can't copy their opponent's sealed move or replay one from an earlier round.
+    pub fn sealed_move(author: &Address, round: u32, move_type: &serde_json::Value, salt: &str) -> Self {
+        Secret {
+            key: format!("round {} {}", round, author),
+            data: move_type.clone(),
+            salt: salt.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Commitment {
+    pub author: Address,
+    pub key: String,
+    pub commitment: Address,
+    pub revealed: Option<serde_json::Value>,
+}
+
+/// The randomness combined from the seeds revealed by every player, in the order of the players in the game.
+/// Each seed was committed to before any was revealed, so no player can choose the result.
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct Randomness {
+    pub key: String,
+    pub seeds: Vec<String>,
+}
+
+impl Randomness {
+    /// The combined value of the seeds
+    pub fn value(&self) -> Address {
+        Entry::App("randomness".into(), self.clone().into()).address()
+    }
+
+    /// Roll `count` dice with the given number of sides, each between 1 and `sides`.
+    /// The same randomness always gives the same rolls so every agent can validate them.
+    pub fn dice(&self, count: usize, sides: u32) -> Vec<u32> {
+        // a splitmix64 generator seeded with a FNV-1a hash of the combined value
+        let mut x = self.value().to_string().bytes()
+            .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3));
+        (0..count).map(|_| {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^= z >> 31;
+            (z % u64::from(sides.max(1))) as u32 + 1
+        }).collect()
+    }
+}
+
+/// Either a meta move or the JSON of a MoveType of the game being played.
+/// Game moves are only interpreted by the GameRules for the game type.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum MoveType {
+    Meta(MetaMove),
+    Game(serde_json::Value),
+}
+
+/// How a game ended. Anything other than InProgress is a terminal state and no more moves can be made.
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub enum Outcome {
+    InProgress,
+    Winner(Address),
+    Draw,
+    Abandoned,
+}
+
+impl Outcome {
+    pub fn is_over(&self) -> bool {
+        *self != Outcome::InProgress
+    }
+}
+
+impl MoveType {
+    pub fn is_game_move(&self) -> bool {
+        match self {
+            MoveType::Game(_) => true,
+            MoveType::Meta(_) => false,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
 pub struct MoveInput {
 	pub game: Address,
@@ -34,6 +232,219 @@ pub struct Move {
 	pub timestamp: u32,
 }
 
+impl MetaMove {
+    pub fn is_valid<S: Clone>(&self, game: &Game, state: &MetaState<S>, author: &Address) -> Result<(), String> {
+        match self {
+            MetaMove::Abandon => {
+                // once the game is underway a player has to resign instead
+                if state.moves.iter().any(|game_move| game_move.move_type.is_game_move()) {
+                    Err("The game has started, it can no longer be abandoned.".into())
+                } else {
+                    Ok(())
+                }
+            },
+            // whether a player's time has run out depends on whose turn it is, see `MetaState::is_valid`
+            MetaMove::ClaimTimeout => Ok(()),
+            MetaMove::Resign => {
+                if game.players.len() > 2 {
+                    Err("Resigning is only possible in two player games.".into())
+                } else {
+                    Ok(())
+                }
+            },
+            MetaMove::ClaimForfeit{evidence} => {
+                let game_address = Entry::App("game".into(), game.clone().into()).address();
+                if game.players.len() > 2 {
+                    Err("A forfeit can only be claimed in two player games.".into())
+                } else if !game.is_player(&evidence.author) || game.seat_of(&evidence.author) == game.seat_of(author) {
+                    Err("A forfeit can only be claimed against your opponent.".into())
+                } else if evidence.moves.len() < 2 {
+                    Err("The evidence of a double move must have at least two moves.".into())
+                } else if evidence.moves.iter().any(|game_move| {
+                    game_move.author != evidence.author || game_move.previous_move != evidence.base || game_move.game != game_address
+                }) {
+                    Err("The moves must all be made by your opponent on the same base in this game.".into())
+                } else if evidence.moves.iter().enumerate().any(|(i, game_move)| evidence.moves[i + 1..].contains(game_move)) {
+                    Err("The moves of a double move must be different.".into())
+                } else {
+                    Ok(())
+                }
+            },
+            MetaMove::OfferDraw => {
+                match &state.draw_offered_by {
+                    Some(_) => Err("A draw has already been offered.".into()),
+                    None => Ok(()),
+                }
+            },
+            MetaMove::AcceptDraw | MetaMove::DeclineDraw => {
+                match &state.draw_offered_by {
+                    Some(offered_by) if offered_by == author => Err("Cannot answer your own draw offer.".into()),
+                    Some(_) if state.draw_accepted_by.contains(author) => Err("You have already accepted the draw.".into()),
+                    Some(_) => Ok(()),
+                    None => Err("No draw has been offered.".into()),
+                }
+            },
+            MetaMove::Commit{key, commitment: _} => {
+                match state.commitment(author, key) {
+                    Some(_) => Err(format!("You have already made a commitment called {}.", key)),
+                    None => Ok(()),
+                }
+            },
+            MetaMove::Reveal{key, data, salt} => {
+                match state.commitment(author, key) {
+                    None => Err(format!("You have not made a commitment called {}.", key)),
+                    Some(commitment) if commitment.revealed.is_some() => Err(format!("The commitment {} has already been revealed.", key)),
+                    Some(commitment) => {
+                        let secret = Secret{key: key.clone(), data: data.clone(), salt: salt.clone()};
+                        if secret.commitment() == commitment.commitment {
+                            Ok(())
+                        } else {
+                            Err(format!("The revealed data does not match the commitment {}.", key))
+                        }
+                    },
+                }
+            },
+            MetaMove::RandomnessRequest{key, commitment: _} => {
+                match state.seed(author, key) {
+                    Some(_) => Err(format!("You have already committed to a seed for {}.", key)),
+                    None => Ok(()),
+                }
+            },
+            MetaMove::RandomnessReveal{key, seed, salt} => {
+                match state.seed(author, key) {
+                    None => Err(format!("You have not committed to a seed for {}.", key)),
+                    Some(commitment) if commitment.revealed.is_some() => Err(format!("Your seed for {} has already been revealed.", key)),
+                    // revealing early would let the last player choose their seed knowing the others
+                    Some(_) if !game.players.iter().all(|player| state.seed(player, key).is_some()) => {
+                        Err(format!("Seeds for {} can only be revealed once every player has committed to one.", key))
+                    },
+                    Some(commitment) => {
+                        if Secret::seed(key, seed, salt).commitment() == commitment.commitment {
+                            Ok(())
+                        } else {
+                            Err(format!("The revealed seed does not match the commitment for {}.", key))
+                        }
+                    },
+                }
+            },
+            // that only the proposer can confirm is checked against their chain, see the move entry validation
+            MetaMove::Confirm => {
+                if game.proposal.is_none() {
+                    Err("Only a game created in response to a proposal needs to be confirmed.".into())
+                } else if !state.moves.is_empty() {
+                    Err("A game can only be confirmed by its first move.".into())
+                } else {
+                    Ok(())
+                }
+            },
+            MetaMove::SealMove{commitment: _} => {
+                match state.sealed_move(author) {
+                    Some(_) => Err("You have already sealed a move for this round.".into()),
+                    None => Ok(()),
+                }
+            },
+            MetaMove::RevealMove{move_type, salt} => {
+                match state.sealed_move(author) {
+                    None => Err("You have not sealed a move for this round.".into()),
+                    Some(sealed) if sealed.revealed.is_some() => Err("Your move for this round has already been revealed.".into()),
+                    Some(_) if !game.players.iter().all(|player| state.sealed_move(player).is_some()) => {
+                        Err("Moves can only be revealed once every player has sealed one.".into())
+                    },
+                    Some(sealed) => {
+                        if Secret::sealed_move(author, state.round, move_type, salt).commitment() == sealed.commitment {
+                            Ok(())
+                        } else {
+                            Err("The revealed move does not match the one you sealed.".into())
+                        }
+                    },
+                }
+            },
+        }
+    }
+}
+
+/// The moves made by the agent who made a link, with their addresses. Links are checked against the
+/// author's chain, which every validator is given in full, as what the DHT holds can differ between them.
+/// The chain also holds the other players' moves, copied in by `make_move`, which are left out.
+fn authored_moves(validation_data: &ValidationData) -> Result<Vec<(Address, Move)>, String> {
+    let local_chain = validation_data.package.source_chain_entries.as_ref()
+        .ok_or("Could not retrieve source chain")?;
+    let sources = validation_data.sources();
+    let moves = local_chain.iter()
+        .filter_map(|entry| match entry {
+            Entry::App(entry_type, entry_data) if entry_type.to_string() == "move" => {
+                Some(Move::try_from(entry_data.clone())
+                    .map(|game_move| (entry.address(), game_move))
+                    .map_err(|_| "Could not read a move in the source chain".to_string()))
+            },
+            _ => None,
+        })
+        .collect::<Result<Vec<(Address, Move)>, String>>()?;
+    Ok(moves.into_iter().filter(|(_, game_move)| sources.contains(&game_move.author)).collect())
+}
+
+/// Whether the chain holds the confirmation of the game as the response to the proposal. Only the author of
+/// the proposal can make that link, and only to one game, see the "confirmed" link validation.
+fn holds_confirmation(local_chain: &[Entry], proposal: &Address, game_address: &Address) -> bool {
+    local_chain.iter().any(|entry| match entry {
+        Entry::LinkAdd(link_data) => {
+            let link = link_data.link();
+            link.link_type() == "confirmed" && link.base() == proposal && link.target() == game_address
+        },
+        _ => false,
+    })
+}
+
+/// A link to a move must come from the game or move the move follows, and is made by the move's author.
+/// An author can only make one move on each base. Two players can still each move on the same base,
+/// this can't be seen from either of their chains and is resolved by `get_moves` instead.
+fn validate_move_link(validation_data: LinkValidationData) -> Result<(), String> {
+    match validation_data {
+        LinkValidationData::LinkAdd{link, validation_data} => {
+            let link = link.link();
+            let moves = authored_moves(&validation_data)?;
+            let next_move = moves.iter()
+                .find(|(address, _)| address == link.target())
+                .map(|(_, next_move)| next_move)
+                .ok_or("A move can only be linked by the agent who made it.")?;
+            if &next_move.previous_move != link.base() {
+                return Err("A move can only be linked from the game or move it follows.".into())
+            }
+            if moves.iter().any(|(address, other)| address != link.target() && &other.previous_move == link.base()) {
+                return Err("You have already made another move after this one, the game can't fork.".into())
+            }
+            Ok(())
+        },
+        LinkValidationData::LinkRemove{..} => {
+            Err("Cannot remove the link between moves".into())
+        },
+    }
+}
+
+/// Every move is also linked from its game, tagged with its index. A fork puts two moves at the same index,
+/// so any number of moves can be linked at an index and `get_moves` picks the canonical one.
+fn validate_indexed_move_link(validation_data: LinkValidationData) -> Result<(), String> {
+    match validation_data {
+        LinkValidationData::LinkAdd{link, validation_data} => {
+            let link = link.link();
+            let next_move = authored_moves(&validation_data)?.into_iter()
+                .find(|(address, _)| address == link.target())
+                .map(|(_, next_move)| next_move)
+                .ok_or("A move can only be linked by the agent who made it.")?;
+            if &next_move.game != link.base() {
+                return Err("A move can only be linked from its own game.".into())
+            }
+            if link.tag().parse::<u32>().is_err() {
+                return Err("A move must be linked from its game with its index as the tag.".into())
+            }
+            Ok(())
+        },
+        LinkValidationData::LinkRemove{..} => {
+            Err("Cannot remove the link between moves".into())
+        },
+    }
+}
+
 pub fn definition() -> ValidatingEntryType {
     entry!(
         name: "move",
@@ -46,23 +457,67 @@ pub fn definition() -> ValidatingEntryType {
         validation: | validation_data: hdk::EntryValidationData<Move>| {
             match validation_data {
                 EntryValidationData::Create{entry, validation_data} => {
+                    let sources = validation_data.sources();
                 	let mut local_chain = validation_data.package.source_chain_entries
                 		.ok_or("Could not retrieve source chain")?;
                 	hdk::debug(format!("{:?}", local_chain))?;
 
                 	// load the game and game state
-                	let _new_move = Move::from(entry);
+                	let _new_move = entry;
+
+                    // the author's clock can be behind but a move can't be made in the future
+                    let header_time = unix_seconds(&validation_data.package.chain_header.timestamp().to_string())
+                        .ok_or("Could not read the time of the move's header")?;
+                    if i64::from(_new_move.timestamp) > header_time {
+                        return Err("The timestamp of a move can't be later than the time it was committed.".into())
+                    }
 
                     // Sometimes the validating entry is already in the chain when validation runs,
                     // To make our state reduction work correctly this must be removed
                     local_chain.remove_item(&Entry::App("move".into() , _new_move.clone().into()));
 
-                	let state = get_state_local_chain(local_chain.clone(), &_new_move.game)
-                		.map_err(|_| "Could not load state during validation")?;
-                	let game = get_game_local_chain(local_chain, &_new_move.game)
+                	let moves = get_moves_local_chain(local_chain.clone(), &_new_move.game)
+                		.map_err(|_| "Could not load moves during validation")?;
+                	let game = get_game_local_chain(local_chain.clone(), &_new_move.game)
                 	    .map_err(|_| "Could not load game during validation")?;
-                    
-                    _new_move.is_valid(game, state)
+                    let snapshots = get_snapshots_local_chain(&local_chain, &_new_move.game);
+
+                    // the evidence can't be looked up on the DHT here, as not every validator may have it yet. One of the
+                    // moves of a double move is on the branch the game follows, so the author holds a copy of it
+                    if let MoveType::Meta(MetaMove::ClaimForfeit{evidence}) = &_new_move.move_type {
+                        if !evidence.moves.iter().any(|game_move| moves.contains(game_move)) {
+                            return Err("One of the moves of a double move must be a move of the game.".into())
+                        }
+                    }
+
+                    // a game made in response to a proposal is only played once the proposer has confirmed it.
+                    // The proposer's Confirm is checked when they commit it, other players hold a copy of it
+                    if let Some(proposal) = &game.proposal {
+                        if moves.first().unwrap_or(&_new_move).move_type != MoveType::Meta(MetaMove::Confirm) {
+                            return Err("This game can only be played once the author of its proposal has confirmed it.".into())
+                        }
+                        if moves.is_empty() && sources.contains(&_new_move.author)
+                            && !holds_confirmation(&local_chain, proposal, &_new_move.game) {
+                            return Err("Only the author of the proposal can confirm this game, once they have chosen it.".into())
+                        }
+                    }
+
+                    // a move must build on the last move the author has seen, or the game if there are none
+                    let expected_previous = match moves.last() {
+                        Some(last_move) => Entry::App("move".into(), last_move.clone().into()).address(),
+                        None => _new_move.game.clone(),
+                    };
+                    if _new_move.previous_move != expected_previous {
+                        return Err("A move must follow the last move of the game.".into())
+                    }
+
+                    dispatch!(game.game_type, G => {
+                        let state = reduce_from_snapshots::<G>(&game, &snapshots, &moves)?;
+                        if state.outcome.is_over() {
+                            return Err(format!("Cannot make a move, the game has already ended: {:?}", state.outcome))
+                        }
+                        state.is_valid::<G>(&game, &_new_move)
+                    })
                 },
                 _ => {
                     Err("Cannot modify or delete a move".into())
@@ -75,20 +530,30 @@ pub fn definition() -> ValidatingEntryType {
                 "game",
                 link_type: "game->move",
                 validation_package: || {
-                    hdk::ValidationPackageDefinition::Entry
+                    hdk::ValidationPackageDefinition::ChainFull
                 },
-                validation: | _validation_data: hdk::LinkValidationData| {
-                    Ok(())
+                validation: | validation_data: hdk::LinkValidationData| {
+                    validate_move_link(validation_data)
                 }
             ),
         	from!(
                 "move",
                 link_type: "move->move",
                 validation_package: || {
-                    hdk::ValidationPackageDefinition::Entry
+                    hdk::ValidationPackageDefinition::ChainFull
                 },
-                validation: | _validation_data: hdk::LinkValidationData| {
-                    Ok(())
+                validation: | validation_data: hdk::LinkValidationData| {
+                    validate_move_link(validation_data)
+                }
+            ),
+        	from!(
+                "game",
+                link_type: "game->moves",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::ChainFull
+                },
+                validation: | validation_data: hdk::LinkValidationData| {
+                    validate_indexed_move_link(validation_data)
                 }
             )
         ]
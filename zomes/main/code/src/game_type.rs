@@ -0,0 +1,660 @@
+use std::fmt::Debug;
+use serde::{Serialize, de::DeserializeOwned};
+use hdk::{
+    AGENT_ADDRESS,
+    holochain_persistence_api::cas::content::Address,
+};
+
+use crate::game::Game;
+use crate::game_move::{Move, MoveType, MetaMove, Outcome, Commitment, Randomness};
+use crate::time_control::{TimeControl, Clock};
+
+/**
+ *
+ * Every game hosted by this DNA implements the `GameRules` trait. The framework takes care of
+ * committing and linking the entries and uses these functions to reduce the moves of a game into
+ * its current state, to validate new moves and to display the game to the players.
+ *
+ * A game only has to describe its own `State` and `MoveType`. Both are stored as JSON so that a single
+ * `move` entry type can carry the moves of any game. Snapshots leave the moves a state keeps out of the
+ * state they store and put them back when it is restored, see `strip_moves` and `restore_moves`.
+ *
+ */
+
+pub trait GameRules {
+    type State: Clone + Debug + Serialize + DeserializeOwned;
+    type MoveType: Clone + Debug + Serialize + DeserializeOwned;
+
+    /// The state of the game before any move has been made
+    fn initial(game: &Game) -> Self::State;
+
+    /// The least and most players the game can be played by
+    fn player_count() -> (usize, usize) {
+        (2, 2)
+    }
+
+    /// Check a new game entry, e.g. that its settings make sense for this game
+    fn validate_game(_game: &Game) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Apply a move to the state. The move can be assumed to be valid, but a state that can't be
+    /// reduced should still be reported as an error rather than a panic.
+    fn evolve(game: &Game, state: &Self::State, next_move: &Move, move_type: &Self::MoveType) -> Result<Self::State, String>;
+
+    /// Ok(()) if the move can be applied to the state, otherwise a message for the player
+    fn is_valid(game: &Game, state: &Self::State, next_move: &Move, move_type: &Self::MoveType) -> Result<(), String>;
+
+    /// A string representation of the state that can be displayed in a terminal
+    fn render(game: &Game, state: &Self::State) -> String;
+
+    /// Whether the state is a win or a draw. Games that can't end on their own can keep the default.
+    fn outcome(_game: &Game, _state: &Self::State) -> Outcome {
+        Outcome::InProgress
+    }
+
+    /// Called when a player commits to hidden information under the given key. Games without hidden
+    /// information keep the default, which rejects commitments.
+    fn commit(_game: &Game, _state: &Self::State, _author: &Address, _key: &str) -> Result<Self::State, String> {
+        Err("This game does not use hidden information.".into())
+    }
+
+    /// Called when a player reveals the data behind a commitment. The framework has already checked it matches.
+    fn reveal(_game: &Game, state: &Self::State, _author: &Address, _key: &str, _data: &serde_json::Value) -> Result<Self::State, String> {
+        Ok(state.clone())
+    }
+
+    /// Called when a player asks for shared randomness under the given key. Games without dice keep the
+    /// default, which rejects the request.
+    fn request_randomness(_game: &Game, _state: &Self::State, _author: &Address, _key: &str) -> Result<Self::State, String> {
+        Err("This game does not use randomness.".into())
+    }
+
+    /// Called once every player has revealed their seed for a key. The game should keep the rolls it
+    /// needs in its state so that `evolve` and `is_valid` can use them.
+    fn randomness(_game: &Game, state: &Self::State, _randomness: &Randomness) -> Result<Self::State, String> {
+        Ok(state.clone())
+    }
+
+    /// Whose turn it is, which is used to run the clocks of games with a time control. Games that keep track
+    /// of it should return it, otherwise the players are assumed to take turns in the order of `Game::players`.
+    fn to_move(_game: &Game, _state: &Self::State) -> Option<Address> {
+        None
+    }
+
+    /// Whether every player moves at once each round. The moves of a round are sealed with SealMove,
+    /// revealed with RevealMove once every player has sealed one and then applied with `evolve_round`.
+    fn simultaneous(_game: &Game) -> bool {
+        false
+    }
+
+    /// Apply the revealed moves of a round, in the order of the players in the game.
+    /// By default they are applied one after the other with `evolve`.
+    fn evolve_round(game: &Game, state: &Self::State, moves: &[(Move, Self::MoveType)]) -> Result<Self::State, String> {
+        moves.iter().try_fold(state.clone(), |state, (next_move, move_type)| Self::evolve(game, &state, next_move, move_type))
+    }
+
+    /// One example of each kind of move the game supports
+    fn describe() -> Vec<Self::MoveType>;
+
+    /// The state as a snapshot stores it, without the moves it keeps, as each snapshot would otherwise repeat
+    /// every move before it. By default these are kept in a `moves` field, a game that keeps them anywhere
+    /// else overrides this along with `restore_moves`.
+    fn strip_moves(mut state: serde_json::Value) -> serde_json::Value {
+        if let Some(moves) = state.get_mut("moves") {
+            *moves = serde_json::Value::Array(Vec::new());
+        }
+        state
+    }
+
+    /// Put the moves back into a state stored by `strip_moves`. These are the moves the rules were given: the
+    /// game moves as made by each seat, or in simultaneous games the revealed moves of each completed round
+    /// in the order of the players.
+    fn restore_moves(mut state: serde_json::Value, moves: &[Move]) -> Result<serde_json::Value, String> {
+        if let Some(state_moves) = state.get_mut("moves") {
+            *state_moves = serde_json::to_value(moves).map_err(|e| e.to_string())?;
+        }
+        Ok(state)
+    }
+}
+
+/// The revealed moves of a round with their parsed move types, as `GameRules::evolve_round` is given them
+type RoundMoves<G> = Vec<(Move, <G as GameRules>::MoveType)>;
+
+/**
+ * DEVCAMP TODO #8:
+ * Add a variant for your own game here and map it to your GameRules implementation in `dispatch!`
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum GameType {
+    TicTacToe,
+    Checkers,
+    Chess,
+    Go,
+    Battleship,
+    RockPaperScissors,
+    Pig,
+}
+
+impl GameType {
+    pub fn all() -> Vec<GameType> {
+        vec![
+            GameType::TicTacToe,
+            GameType::Checkers,
+            GameType::Chess,
+            GameType::Go,
+            GameType::Battleship,
+            GameType::RockPaperScissors,
+            GameType::Pig,
+        ]
+    }
+}
+
+/// Evaluate an expression with `$rules` bound to the GameRules implementation of the given game type
+macro_rules! dispatch {
+    ($game_type:expr, $rules:ident => $body:expr) => {
+        match $game_type {
+            $crate::game_type::GameType::TicTacToe => {
+                type $rules = $crate::tictactoe::TicTacToe;
+                $body
+            },
+            $crate::game_type::GameType::Checkers => {
+                type $rules = $crate::checkers::Checkers;
+                $body
+            },
+            $crate::game_type::GameType::Chess => {
+                type $rules = $crate::chess::Chess;
+                $body
+            },
+            $crate::game_type::GameType::Go => {
+                type $rules = $crate::go::Go;
+                $body
+            },
+            $crate::game_type::GameType::Battleship => {
+                type $rules = $crate::battleship::Battleship;
+                $body
+            },
+            $crate::game_type::GameType::RockPaperScissors => {
+                type $rules = $crate::rockpaperscissors::RockPaperScissors;
+                $body
+            },
+            $crate::game_type::GameType::Pig => {
+                type $rules = $crate::pig::Pig;
+                $body
+            },
+        }
+    };
+}
+
+/**
+ *
+ * The framework wraps the state of each game with the things every game has in common:
+ * all of the moves made so far, how the game ended, any pending draw offer, the commitments
+ * to hidden information made by the players, the seeds committed to for shared randomness and,
+ * in simultaneous games, the moves sealed for the current round. For games with a time control
+ * it also runs the players' clocks.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MetaState<S> {
+    pub moves: Vec<Move>,
+    pub outcome: Outcome,
+    pub draw_offered_by: Option<Address>,
+    /// The players who have accepted the draw on offer, it is only agreed once every other player has
+    pub draw_accepted_by: Vec<Address>,
+    pub commitments: Vec<Commitment>,
+    pub seeds: Vec<Commitment>,
+    pub round: u32,
+    pub sealed_moves: Vec<Commitment>,
+    pub clocks: Vec<Clock>,
+    /// The timestamp of the last move, clocks only run once the first move has been made
+    pub last_move_at: Option<u32>,
+    pub game_state: S,
+}
+
+impl<S: Clone> MetaState<S> {
+    pub fn initial(game: &Game, game_state: S) -> Self {
+        let clocks = match &game.time_control {
+            Some(time_control) => game.players.iter()
+                .map(|player| Clock{player: player.clone(), remaining: time_control.initial_seconds()})
+                .collect(),
+            None => Vec::new(),
+        };
+        MetaState {
+            moves: Vec::new(),
+            outcome: Outcome::InProgress,
+            draw_offered_by: None,
+            draw_accepted_by: Vec::new(),
+            commitments: Vec::new(),
+            seeds: Vec::new(),
+            round: 0,
+            sealed_moves: Vec::new(),
+            clocks,
+            last_move_at: None,
+            game_state,
+        }
+    }
+
+    pub fn commitment(&self, author: &Address, key: &str) -> Option<&Commitment> {
+        self.commitments.iter().find(|commitment| &commitment.author == author && commitment.key == key)
+    }
+
+    pub fn seed(&self, author: &Address, key: &str) -> Option<&Commitment> {
+        self.seeds.iter().find(|seed| &seed.author == author && seed.key == key)
+    }
+
+    /// The randomness for a key once every player has revealed their seed
+    pub fn randomness(&self, game: &Game, key: &str) -> Option<Randomness> {
+        let seeds: Option<Vec<String>> = game.players.iter()
+            .map(|player| {
+                self.seed(player, key)
+                    .and_then(|seed| seed.revealed.as_ref())
+                    .and_then(|revealed| revealed.as_str().map(String::from))
+            })
+            .collect();
+        seeds.map(|seeds| Randomness{key: key.to_string(), seeds})
+    }
+
+    /// True once every player but the one who offered the draw has accepted it
+    fn draw_agreed(&self, game: &Game) -> bool {
+        match &self.draw_offered_by {
+            Some(offered_by) => game.players.iter()
+                .filter(|player| *player != offered_by)
+                .all(|player| self.draw_accepted_by.contains(player)),
+            None => false,
+        }
+    }
+
+    pub fn sealed_move(&self, author: &Address) -> Option<&Commitment> {
+        self.sealed_moves.iter().find(|sealed| &sealed.author == author)
+    }
+
+    /// The revealed moves of the current round in the order of the players, once every player has revealed theirs
+    fn round_moves<G: GameRules<State = S>>(&self, game: &Game) -> Result<Option<RoundMoves<G>>, String> {
+        let mut round_moves = Vec::new();
+        for player in game.players.iter() {
+            let revealed = self.sealed_move(player).and_then(|sealed| sealed.revealed.as_ref());
+            let reveal_move = self.moves.iter().rev().find(|game_move| {
+                &game.seat_of(&game_move.author) == player && match game_move.move_type {
+                    MoveType::Meta(MetaMove::RevealMove{..}) => true,
+                    _ => false,
+                }
+            });
+            match (revealed, reveal_move) {
+                (Some(move_json), Some(reveal_move)) => round_moves.push((game.seated(reveal_move), parse_move::<G>(move_json)?)),
+                _ => return Ok(None),
+            }
+        }
+        Ok(Some(round_moves))
+    }
+
+    pub fn has_unrevealed_commitments(&self) -> bool {
+        self.commitments.iter().any(|commitment| commitment.revealed.is_none())
+    }
+
+    /// The outcome according to the game rules. A finished game stays in progress until every commitment is revealed.
+    fn settle_outcome<G: GameRules<State = S>>(&mut self, game: &Game) {
+        self.outcome = if self.has_unrevealed_commitments() {
+            Outcome::InProgress
+        } else {
+            G::outcome(game, &self.game_state)
+        };
+    }
+
+    /// True once the game rules say the game is over but the framework is still waiting for reveals
+    pub fn awaiting_reveals<G: GameRules<State = S>>(&self, game: &Game) -> bool {
+        self.has_unrevealed_commitments() && G::outcome(game, &self.game_state).is_over()
+    }
+
+    /// The players the game is waiting on to move
+    pub fn waiting_for<G: GameRules<State = S>>(&self, game: &Game) -> Vec<Address> {
+        if self.awaiting_reveals::<G>(game) {
+            return game.players.iter()
+                .filter(|player| self.commitments.iter().any(|commitment| &commitment.author == *player && commitment.revealed.is_none()))
+                .cloned()
+                .collect()
+        }
+        if G::simultaneous(game) {
+            let all_sealed = game.players.iter().all(|player| self.sealed_move(player).is_some());
+            return game.players.iter()
+                .filter(|player| match self.sealed_move(player) {
+                    None => true,
+                    Some(sealed) => all_sealed && sealed.revealed.is_none(),
+                })
+                .cloned()
+                .collect()
+        }
+        let to_move = G::to_move(game, &self.game_state).unwrap_or_else(|| {
+            match self.moves.iter().rev().find(|game_move| game_move.move_type.is_game_move()) {
+                Some(last_move) => game.next_player(&game.seat_of(&last_move.author)),
+                None => game.first_player(),
+            }
+        });
+        vec![to_move]
+    }
+
+    /// The players the game is waiting on whose time has run out at the given time
+    pub fn out_of_time<G: GameRules<State = S>>(&self, game: &Game, now: u32) -> Vec<Address> {
+        let elapsed = match self.last_move_at {
+            Some(last_move_at) => i64::from(now) - i64::from(last_move_at),
+            None => return Vec::new(),
+        };
+        let waiting_for = self.waiting_for::<G>(game);
+        self.clocks.iter()
+            .filter(|clock| waiting_for.contains(&clock.player) && clock.remaining < elapsed)
+            .map(|clock| clock.player.clone())
+            .collect()
+    }
+
+    /// Charge the time since the last move to the players that were being waited on. Every move, meta moves
+    /// included, moves the clocks on to its timestamp, so no time is charged twice. Returns who was waited on.
+    fn run_clocks<G: GameRules<State = S>>(&mut self, game: &Game, next_move: &Move) -> Vec<Address> {
+        // the clocks start with the first move played, not with the proposer confirming the game
+        if game.time_control.is_none() || next_move.move_type == MoveType::Meta(MetaMove::Confirm) {
+            return Vec::new()
+        }
+        let waiting_for = self.waiting_for::<G>(game);
+        let elapsed = self.last_move_at.map_or(0, |last_move_at| i64::from(next_move.timestamp) - i64::from(last_move_at));
+        for clock in self.clocks.iter_mut().filter(|clock| waiting_for.contains(&clock.player)) {
+            clock.remaining -= elapsed;
+        }
+        self.last_move_at = Some(next_move.timestamp);
+        waiting_for
+    }
+
+    /// Once a move ends its author's turn their clock is reset or given its increment. A move that leaves the
+    /// game still waiting on its author, like a draw offer or the first jump of several, doesn't end the turn.
+    fn end_turn<G: GameRules<State = S>>(&mut self, game: &Game, next_move: &Move, waited_on: &[Address]) {
+        let time_control = match &game.time_control {
+            Some(time_control) => time_control,
+            None => return,
+        };
+        if !waited_on.contains(&next_move.author) || self.waiting_for::<G>(game).contains(&next_move.author) {
+            return
+        }
+        for clock in self.clocks.iter_mut().filter(|clock| clock.player == next_move.author) {
+            match time_control {
+                TimeControl::PerMove{seconds} => clock.remaining = i64::from(*seconds),
+                TimeControl::Total{increment, ..} => clock.remaining += i64::from(*increment),
+            }
+        }
+    }
+
+    pub fn evolve<G: GameRules<State = S>>(&self, game: &Game, next_move: &Move) -> Result<Self, String> {
+        let mut new_state = self.clone();
+        new_state.moves.push(next_move.clone());
+        // in a team game the rules only ever see the team's seat make a move
+        let next_move = &game.seated(next_move);
+        let waited_on = new_state.run_clocks::<G>(game, next_move);
+
+        match &next_move.move_type {
+            MoveType::Meta(MetaMove::Abandon) => {
+                new_state.outcome = Outcome::Abandoned;
+            },
+            MoveType::Meta(MetaMove::Resign) => {
+                new_state.outcome = Outcome::Winner(game.next_player(&next_move.author));
+            },
+            MoveType::Meta(MetaMove::ClaimTimeout) | MoveType::Meta(MetaMove::ClaimForfeit{..}) => {
+                new_state.outcome = Outcome::Winner(next_move.author.clone());
+            },
+            MoveType::Meta(MetaMove::OfferDraw) => {
+                new_state.draw_offered_by = Some(next_move.author.clone());
+                new_state.draw_accepted_by = Vec::new();
+            },
+            MoveType::Meta(MetaMove::AcceptDraw) => {
+                new_state.draw_accepted_by.push(next_move.author.clone());
+                if new_state.draw_agreed(game) {
+                    new_state.draw_offered_by = None;
+                    new_state.draw_accepted_by = Vec::new();
+                    new_state.outcome = Outcome::Draw;
+                }
+            },
+            MoveType::Meta(MetaMove::DeclineDraw) => {
+                new_state.draw_offered_by = None;
+                new_state.draw_accepted_by = Vec::new();
+            },
+            MoveType::Meta(MetaMove::Confirm) => {},
+            MoveType::Meta(MetaMove::Commit{key, commitment}) => {
+                new_state.game_state = G::commit(game, &self.game_state, &next_move.author, key)?;
+                new_state.commitments.push(Commitment {
+                    author: next_move.author.clone(),
+                    key: key.clone(),
+                    commitment: commitment.clone(),
+                    revealed: None,
+                });
+            },
+            MoveType::Meta(MetaMove::Reveal{key, data, salt: _}) => {
+                new_state.game_state = G::reveal(game, &self.game_state, &next_move.author, key, data)?;
+                new_state.commitments.iter_mut()
+                    .filter(|commitment| commitment.author == next_move.author && &commitment.key == key)
+                    .for_each(|commitment| commitment.revealed = Some(data.clone()));
+                new_state.settle_outcome::<G>(game);
+            },
+            MoveType::Meta(MetaMove::RandomnessRequest{key, commitment}) => {
+                new_state.game_state = G::request_randomness(game, &self.game_state, &next_move.author, key)?;
+                new_state.seeds.push(Commitment {
+                    author: next_move.author.clone(),
+                    key: key.clone(),
+                    commitment: commitment.clone(),
+                    revealed: None,
+                });
+            },
+            MoveType::Meta(MetaMove::RandomnessReveal{key, seed, salt: _}) => {
+                new_state.seeds.iter_mut()
+                    .filter(|commitment| commitment.author == next_move.author && &commitment.key == key)
+                    .for_each(|commitment| commitment.revealed = Some(serde_json::Value::String(seed.clone())));
+                if let Some(randomness) = new_state.randomness(game, key) {
+                    new_state.game_state = G::randomness(game, &new_state.game_state, &randomness)?;
+                    new_state.settle_outcome::<G>(game);
+                }
+            },
+            MoveType::Meta(MetaMove::SealMove{commitment}) => {
+                new_state.sealed_moves.push(Commitment {
+                    author: next_move.author.clone(),
+                    key: format!("round {}", self.round),
+                    commitment: commitment.clone(),
+                    revealed: None,
+                });
+            },
+            MoveType::Meta(MetaMove::RevealMove{move_type, salt: _}) => {
+                new_state.sealed_moves.iter_mut()
+                    .filter(|sealed| sealed.author == next_move.author)
+                    .for_each(|sealed| sealed.revealed = Some(move_type.clone()));
+                if let Some(round_moves) = new_state.round_moves::<G>(game)? {
+                    new_state.game_state = G::evolve_round(game, &self.game_state, &round_moves)?;
+                    new_state.round += 1;
+                    new_state.sealed_moves = Vec::new();
+                    new_state.settle_outcome::<G>(game);
+                }
+            },
+            MoveType::Game(move_json) => {
+                let move_type = parse_move::<G>(move_json)?;
+                new_state.game_state = G::evolve(game, &self.game_state, next_move, &move_type)?;
+                new_state.settle_outcome::<G>(game);
+                // playing on instead of answering a draw offer declines it
+                if self.draw_offered_by.as_ref().map_or(false, |offered_by| offered_by != &next_move.author) {
+                    new_state.draw_offered_by = None;
+                    new_state.draw_accepted_by = Vec::new();
+                }
+            },
+        }
+        new_state.end_turn::<G>(game, next_move, &waited_on);
+        Ok(new_state)
+    }
+
+    pub fn is_valid<G: GameRules<State = S>>(&self, game: &Game, next_move: &Move) -> Result<(), String> {
+        if !game.is_player(&next_move.author) {
+            return Err("Player is not part of this game!".into())
+        }
+        if let Some(team) = game.team_of(&next_move.author) {
+            if next_move.move_type.is_game_move() {
+                let moves_made = self.moves.iter()
+                    .filter(|game_move| game_move.move_type.is_game_move() && team.members.contains(&game_move.author))
+                    .count();
+                match team.member_to_move(moves_made) {
+                    Some(member) if member != &next_move.author => {
+                        return Err(format!("It is {}'s turn to move for team {}.", member, team.name))
+                    },
+                    _ => {},
+                }
+            }
+        }
+        let next_move = &game.seated(next_move);
+        let last_move_at = self.moves.last().map_or(game.created_at, |last_move| last_move.timestamp);
+        if next_move.timestamp < last_move_at {
+            return Err("The timestamp of a move can't be earlier than the move before it.".into())
+        }
+        if self.awaiting_reveals::<G>(game) {
+            match &next_move.move_type {
+                // a player who won't reveal can still lose on time
+                MoveType::Meta(MetaMove::Reveal{..}) | MoveType::Meta(MetaMove::ClaimTimeout) => {},
+                _ => return Err("The game is over but has unrevealed commitments, only Reveal moves can be made.".into()),
+            }
+        }
+        match &next_move.move_type {
+            MoveType::Meta(meta_move) => {
+                meta_move.is_valid(game, self, &next_move.author)?;
+                // the game may also have rules about what can be committed to and revealed
+                match meta_move {
+                    MetaMove::Commit{key, commitment: _} => G::commit(game, &self.game_state, &next_move.author, key).map(|_| ()),
+                    MetaMove::Reveal{key, data, salt: _} => G::reveal(game, &self.game_state, &next_move.author, key, data).map(|_| ()),
+                    MetaMove::RandomnessRequest{key, commitment: _} => G::request_randomness(game, &self.game_state, &next_move.author, key).map(|_| ()),
+                    MetaMove::RandomnessReveal{..} => self.evolve::<G>(game, next_move).map(|_| ()),
+                    MetaMove::SealMove{..} if !G::simultaneous(game) => Err("This game is not played in simultaneous rounds.".into()),
+                    MetaMove::ClaimTimeout => {
+                        let out_of_time: Vec<Address> = self.out_of_time::<G>(game, next_move.timestamp).into_iter()
+                            .filter(|player| player != &next_move.author)
+                            .collect();
+                        if game.time_control.is_none() {
+                            Err("This game is not played with a time control.".into())
+                        } else if out_of_time.is_empty() {
+                            Err("Your opponent still has time left on their clock.".into())
+                        } else {
+                            Ok(())
+                        }
+                    },
+                    MetaMove::RevealMove{move_type, salt: _} => {
+                        // a sealed move must still follow the rules of the game once it is revealed
+                        let move_type = parse_move::<G>(move_type)?;
+                        G::is_valid(game, &self.game_state, next_move, &move_type)
+                    },
+                    _ => Ok(()),
+                }
+            },
+            MoveType::Game(_) if G::simultaneous(game) => {
+                Err("Every player moves at once in this game, seal your move with SealMove.".into())
+            },
+            MoveType::Game(move_json) => {
+                let move_type = parse_move::<G>(move_json)?;
+                G::is_valid(game, &self.game_state, next_move, &move_type)
+            },
+        }
+    }
+
+    pub fn render<G: GameRules<State = S>>(&self, game: &Game) -> String {
+        let mut disp = G::render(game, &self.game_state);
+        // a team member sees the game from their team's seat
+        let me = game.seat_of(&AGENT_ADDRESS.to_string().into());
+
+        let unrevealed: Vec<&Commitment> = self.commitments.iter().filter(|commitment| commitment.revealed.is_none()).collect();
+        if unrevealed.iter().any(|commitment| commitment.author == me) {
+            disp.push_str("You have commitments that must be revealed before the game can end\n");
+        } else if !unrevealed.is_empty() {
+            disp.push_str("Your opponent has commitments that must be revealed before the game can end\n");
+        }
+
+        let mut pending_keys: Vec<&String> = self.seeds.iter().filter(|seed| seed.revealed.is_none()).map(|seed| &seed.key).collect();
+        pending_keys.sort();
+        pending_keys.dedup();
+        for key in pending_keys {
+            match self.seed(&me, key) {
+                None => disp.push_str(&format!("Your opponent has asked for randomness {}, answer with a RandomnessRequest\n", key)),
+                Some(seed) if seed.revealed.is_none() => {
+                    disp.push_str(&format!("Reveal your seed for {} with a RandomnessReveal once every player has committed to one\n", key))
+                },
+                Some(_) => disp.push_str(&format!("Waiting for your opponent's seed for {}\n", key)),
+            }
+        }
+
+        match self.sealed_move(&me) {
+            None if !self.sealed_moves.is_empty() => disp.push_str("Your opponent has sealed their move for this round, seal yours with SealMove\n"),
+            None => {},
+            Some(sealed) if sealed.revealed.is_none() => disp.push_str("Reveal your move with RevealMove once every player has sealed one\n"),
+            Some(_) => disp.push_str("Waiting for your opponent to reveal their move\n"),
+        }
+
+        if !self.clocks.is_empty() {
+            let clocks: Vec<String> = self.clocks.iter().enumerate().map(|(i, clock)| {
+                let name = if clock.player == me { "you".to_string() } else { format!("player {}", i + 1) };
+                let remaining = clock.remaining.max(0);
+                format!("{} {}:{:02}", name, remaining / 60, remaining % 60)
+            }).collect();
+            disp.push_str(&format!("Time left at the last move: {}\n", clocks.join(", ")));
+        }
+
+        if let Some(offered_by) = &self.draw_offered_by {
+            if offered_by == &me {
+                disp.push_str("You have offered a draw\n");
+            } else if self.draw_accepted_by.contains(&me) {
+                disp.push_str("You have accepted the draw, waiting for the other players to answer\n");
+            } else {
+                disp.push_str("Your opponent has offered a draw, answer with AcceptDraw or DeclineDraw\n");
+            }
+        }
+
+        let resigned = self.moves.last().map_or(false, |last_move| last_move.move_type == MoveType::Meta(MetaMove::Resign));
+        match &self.outcome {
+            Outcome::InProgress => {},
+            Outcome::Draw => disp.push_str("Game over: The game is a draw!\n"),
+            Outcome::Abandoned => disp.push_str("Game over: The game was abandoned!\n"),
+            Outcome::Winner(winner) => {
+                match (winner == &me, resigned) {
+                    (true, true) => disp.push_str("Game over: Your opponent has resigned!\n"),
+                    (true, false) => disp.push_str("Game over: You are the winner!\n"),
+                    (false, true) => disp.push_str("Game over: You have resigned!\n"),
+                    (false, false) => disp.push_str("Game over: Your opponent is the winner!\n"),
+                }
+            },
+        }
+        disp
+    }
+}
+
+/*========================================
+=            Helper functions            =
+========================================*/
+
+/// Read the game specific move out of the JSON of a game move
+pub fn parse_move<G: GameRules>(move_json: &serde_json::Value) -> Result<G::MoveType, String> {
+    serde_json::from_value(move_json.clone())
+        .map_err(|e| format!("Move is not a valid move for this game: {}", e))
+}
+
+/// Fold a list of moves into the state they produce, starting from the initial state
+pub fn reduce_state<G: GameRules>(game: &Game, moves: &[Move]) -> Result<MetaState<G::State>, String> {
+    let mut initial = MetaState::initial(game, G::initial(game));
+    // a game can start from a position that is already over, e.g. a finished game imported into chess
+    initial.settle_outcome::<G>(game);
+    moves.iter().try_fold(initial, |state, next_move| {
+        state.evolve::<G>(game, next_move)
+    })
+}
+
+/// Ok(()) if it is the author's turn according to `GameRules::to_move`. Games call this from `is_valid`
+/// for the moves that have to be made in turn. A game that doesn't say whose turn it is has no turns to check.
+pub fn is_players_turn<G: GameRules>(game: &Game, state: &G::State, author: &Address) -> Result<(), String> {
+    match G::to_move(game, state) {
+        Some(to_move) if &to_move != author => Err("It is not this players turn.".into()),
+        _ => Ok(()),
+    }
+}
+
+/// The moves of the game followed by the meta moves every game supports
+pub fn describe_moves<G: GameRules>() -> Vec<MoveType> {
+    G::describe().iter()
+        .map(|move_type| MoveType::Game(serde_json::to_value(move_type).unwrap_or(serde_json::Value::Null)))
+        .chain(MetaMove::describe().into_iter().map(MoveType::Meta))
+        .collect()
+}
+
+/*=====  End of Helper functions  ======*/
@@ -0,0 +1,64 @@
+/**
+ * All of this code is specific to the game Go
+ * By changing the moves, state, reducer and validation rules you can implement you own game.
+ */
+
+pub mod state;
+pub mod validation;
+pub mod moves;
+pub mod sgf;
+
+pub use self::{
+    state::{
+        GameState,
+        Settings,
+    },
+    moves::{
+        MoveType,
+    },
+};
+
+use hdk::holochain_persistence_api::cas::content::Address;
+
+use crate::game::Game;
+use crate::game_move::{Move, Outcome};
+use crate::game_type::GameRules;
+
+pub struct Go;
+
+impl GameRules for Go {
+    type State = GameState;
+    type MoveType = MoveType;
+
+    fn initial(game: &Game) -> GameState {
+        GameState::initial(game)
+    }
+
+    fn evolve(game: &Game, state: &GameState, next_move: &Move, move_type: &MoveType) -> Result<GameState, String> {
+        Ok(state.evolve(game.clone(), next_move, move_type))
+    }
+
+    fn is_valid(game: &Game, state: &GameState, next_move: &Move, move_type: &MoveType) -> Result<(), String> {
+        move_type.is_valid(game.clone(), state.clone(), &next_move.author)
+    }
+
+    fn render(_game: &Game, state: &GameState) -> String {
+        state.render()
+    }
+
+    fn validate_game(game: &Game) -> Result<(), String> {
+        game.try_settings::<Settings>()?.is_valid()
+    }
+
+    fn outcome(_game: &Game, state: &GameState) -> Outcome {
+        state.outcome.clone()
+    }
+
+    fn to_move(_game: &Game, state: &GameState) -> Option<Address> {
+        Some(state.to_move.clone())
+    }
+
+    fn describe() -> Vec<MoveType> {
+        MoveType::describe()
+    }
+}
@@ -0,0 +1,47 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+
+
+/**
+ *
+ * The MoveType enum defines all the types of moves that are valid in your game and the
+ * data they carry. In Go you place a stone on an empty point or pass.
+ *
+ * Once both players pass in a row the game moves on to scoring. Either player proposes which stones
+ * are dead (MarkDead lists one stone of each dead group) and the other player accepts the
+ * proposal (AcceptScore), which ends the game. If the players can't agree either of them can
+ * resume play to settle the position on the board.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub enum MoveType {
+    Place {
+        pos: Point,
+    },
+    Pass,
+    MarkDead {
+        stones: Vec<Point>,
+    },
+    AcceptScore,
+    Resume,
+}
+
+impl MoveType {
+	pub fn describe() -> Vec<MoveType> {
+		vec![
+			MoveType::Place{pos: Point{x: 3, y: 3}},
+			MoveType::Pass,
+			MoveType::MarkDead{stones: vec![Point{x: 0, y: 0}]},
+			MoveType::AcceptScore,
+			MoveType::Resume,
+		]
+	}
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
@@ -0,0 +1,77 @@
+use crate::game_move::{MoveType as FrameworkMoveType, Outcome};
+use crate::game::Game;
+use crate::game_type::parse_move;
+use super::{
+    Go,
+    GameState,
+    MoveType,
+    moves::Point,
+    state::{Scoring, BLACK},
+};
+
+/**
+ *
+ * Smart Game Format is the standard format for recording Go games so they can be reviewed in other software.
+ * Points are written as two letters, the column then the row counting from the top left ("aa").
+ *
+ */
+
+/// Export a game with all of the moves made so far
+pub fn to_sgf(game: &Game, state: &GameState, outcome: &Outcome) -> String {
+    let (year, month, day) = game.created_date();
+    let rules = match state.settings.scoring {
+        Scoring::Area => "Chinese",
+        Scoring::Territory => "Japanese",
+    };
+    let mut sgf = format!(
+        "(;FF[4]GM[1]CA[UTF-8]AP[generic-game]SZ[{}]KM[{}]RU[{}]DT[{:04}-{:02}-{:02}]PB[{}]PW[{}]",
+        state.settings.board_size, state.settings.komi, rules, year, month, day, state.black, state.white,
+    );
+    if let Some(result) = sgf_result(state, outcome) {
+        sgf.push_str(&format!("RE[{}]", result));
+    }
+
+    for next_move in state.moves.iter() {
+        let colour = if state.colour_of(&next_move.author) == BLACK { "B" } else { "W" };
+        if let FrameworkMoveType::Game(move_json) = &next_move.move_type {
+            match parse_move::<Go>(move_json) {
+                Ok(MoveType::Place{pos}) => sgf.push_str(&format!("\n;{}[{}]", colour, sgf_point(pos))),
+                Ok(MoveType::Pass) => sgf.push_str(&format!("\n;{}[]", colour)),
+                _ => {},
+            }
+        }
+    }
+
+    // record the agreed dead stones as territory markup on the final position
+    if state.score.is_some() && !state.dead_stones.is_empty() {
+        let dead: String = state.dead_stones.iter().map(|stone| format!("[{}]", sgf_point(*stone))).collect();
+        sgf.push_str(&format!("\n;C[Dead stones]MA{}", dead));
+    }
+    sgf.push_str(")\n");
+    sgf
+}
+
+/*========================================
+=            Helper functions            =
+========================================*/
+
+fn sgf_point(point: Point) -> String {
+    format!("{}{}", (b'a' + point.x as u8) as char, (b'a' + point.y as u8) as char)
+}
+
+fn sgf_result(state: &GameState, outcome: &Outcome) -> Option<String> {
+    match outcome {
+        Outcome::InProgress => None,
+        Outcome::Draw => Some("0".into()),
+        Outcome::Abandoned => Some("Void".into()),
+        Outcome::Winner(winner) => {
+            let colour = if winner == &state.black { "B" } else { "W" };
+            match &state.score {
+                Some(score) => Some(format!("{}+{}", colour, (score.black - score.white).abs())),
+                None => Some(format!("{}+R", colour)),
+            }
+        },
+    }
+}
+
+/*=====  End of Helper functions  ======*/
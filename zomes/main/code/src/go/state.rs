@@ -0,0 +1,367 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+use hdk::holochain_persistence_api::cas::content::Address;
+use hdk::AGENT_ADDRESS;
+
+use crate::game_move::{Move, Outcome};
+use crate::game::Game;
+use super::{
+    moves::Point,
+    MoveType,
+};
+
+pub const BLACK_MARK: char = 'X';  // black goes first
+pub const WHITE_MARK: char = 'O';
+pub const BLACK_DEAD_MARK: char = 'x';
+pub const WHITE_DEAD_MARK: char = 'o';
+pub const EMPTY_SPACE: char = '.';
+
+/// The contents of a point in the dense board representation
+pub const EMPTY: u8 = 0;
+pub const BLACK: u8 = 1;
+pub const WHITE: u8 = 2;
+
+pub type Board = Vec<Vec<u8>>;
+
+/**
+ *
+ * The board size, komi and rules are chosen when the game is created, e.g. {"board_size": 9, "komi": 5.5}.
+ *
+ * Area scoring counts stones and surrounded points, territory scoring counts surrounded points and prisoners.
+ * With superko a move may never recreate an earlier position, otherwise only the immediate recapture of a ko is forbidden.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Scoring {
+    Area,
+    Territory,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub board_size: usize,
+    pub komi: f64,
+    pub scoring: Scoring,
+    pub superko: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            board_size: 19,
+            komi: 6.5,
+            scoring: Scoring::Area,
+            superko: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn is_valid(&self) -> Result<(), String> {
+        if ![9, 13, 19].contains(&self.board_size) {
+            Err("Board size must be 9, 13 or 19".into())
+        } else if (self.komi * 2.0).fract() != 0.0 {
+            Err("Komi must be a whole or half point".into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/**
+ *
+ * The state holds the moves made so far and the current board. It also keeps every earlier board
+ * position so that ko can be enforced, and the stones captured by each player for territory scoring.
+ *
+ * The second player in the game plays black and moves first.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Phase {
+    Playing,
+    Scoring,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct Score {
+    pub black: f64,
+    pub white: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct GameState {
+    pub settings: Settings,
+    pub moves: Vec<Move>,
+    pub black: Address,
+    pub white: Address,
+    pub board: Board,
+    pub to_move: Address,
+    pub phase: Phase,
+    pub consecutive_passes: u32,
+    /// the board after every move, oldest first
+    pub history: Vec<String>,
+    pub black_captures: usize,
+    pub white_captures: usize,
+    pub dead_stones: Vec<Point>,
+    pub marked_by: Option<Address>,
+    pub score: Option<Score>,
+    pub outcome: Outcome,
+}
+
+impl GameState {
+    pub fn initial(game: &Game) -> Self {
+        let settings: Settings = game.settings();
+        let board = vec![vec![EMPTY; settings.board_size]; settings.board_size];
+        GameState {
+            moves: Vec::new(),
+            black: game.players[1].clone(),
+            white: game.players[0].clone(),
+            history: vec![board_key(&board)],
+            board,
+            to_move: game.first_player(),
+            phase: Phase::Playing,
+            consecutive_passes: 0,
+            black_captures: 0,
+            white_captures: 0,
+            dead_stones: Vec::new(),
+            marked_by: None,
+            score: None,
+            outcome: Outcome::InProgress,
+            settings,
+        }
+    }
+
+    pub fn colour_of(&self, player: &Address) -> u8 {
+        if player == &self.black { BLACK } else { WHITE }
+    }
+
+    pub fn render(&self) -> String {
+        let mut disp = "\n".to_string();
+        let me: Address = AGENT_ADDRESS.to_string().into();
+
+        if self.outcome.is_over() {
+            disp.push_str("The game has ended \n");
+        } else if self.phase == Phase::Scoring {
+            match &self.marked_by {
+                Some(marked_by) if marked_by == &me => disp.push_str("Waiting for your opponent to accept the dead stones \n"),
+                Some(_) => disp.push_str("Your opponent has marked the dead stones, AcceptScore, MarkDead or Resume \n"),
+                None => disp.push_str("Both players passed, mark the dead stones with MarkDead \n"),
+            }
+        } else if self.to_move == me {
+            disp.push_str("It is your turn \n");
+        } else if self.moves.is_empty() {
+            disp.push_str("Your opponent moves first \n");
+        } else {
+            disp.push_str("It is your opponents turn \n");
+        }
+        disp.push('\n');
+
+        // width of the widest coordinate so larger boards stay aligned
+        let size = self.settings.board_size;
+        let w = (size - 1).to_string().len();
+        let header: String = (0..size).map(|x| format!("{:<w$} ", x, w = w)).collect();
+        disp.push_str(&format!("{:w$}  x  {}\ny\n", "", header.trim_end(), w = w - 1));
+        for y in 0..size {
+            disp.push_str(&format!("{:<w$}   |", y, w = w));
+            for x in 0..size {
+                let dead = self.dead_stones.contains(&Point{x, y});
+                let c = match (self.board[x][y], dead) {
+                    (BLACK, false) => BLACK_MARK,
+                    (BLACK, true) => BLACK_DEAD_MARK,
+                    (WHITE, false) => WHITE_MARK,
+                    (WHITE, true) => WHITE_DEAD_MARK,
+                    _ => EMPTY_SPACE,
+                };
+                disp.push_str(&format!("{:^w$}|", c, w = w));
+            }
+            disp.push('\n');
+        }
+
+        disp.push_str(&format!("\nCaptures - black: {}, white: {}\n", self.black_captures, self.white_captures));
+        if self.phase == Phase::Scoring || self.outcome.is_over() {
+            let score = self.score.clone().unwrap_or_else(|| self.count_score());
+            disp.push_str(&format!("Score - black: {}, white: {} (komi {})\n", score.black, score.white, self.settings.komi));
+        }
+        disp
+    }
+
+    pub fn evolve(&self, game: Game, next_move: &Move, move_type: &MoveType) -> Self {
+        let mut state = self.clone();
+        state.moves.push(next_move.to_owned());
+
+        match move_type {
+            MoveType::Place{pos} => {
+                let colour = self.colour_of(&next_move.author);
+                let (board, captured) = place_stone(&self.board, *pos, colour);
+                if colour == BLACK {
+                    state.black_captures += captured;
+                } else {
+                    state.white_captures += captured;
+                }
+                state.board = board;
+                state.consecutive_passes = 0;
+                state.to_move = game.next_player(&next_move.author);
+            },
+            MoveType::Pass => {
+                state.consecutive_passes += 1;
+                state.to_move = game.next_player(&next_move.author);
+                if state.consecutive_passes >= 2 {
+                    state.phase = Phase::Scoring;
+                }
+            },
+            MoveType::MarkDead{stones} => {
+                // marking any stone of a group marks the whole group
+                let mut dead_stones: Vec<Point> = Vec::new();
+                for stone in stones {
+                    if !dead_stones.contains(stone) {
+                        dead_stones.extend(group(&self.board, *stone).0);
+                    }
+                }
+                state.dead_stones = dead_stones;
+                state.marked_by = Some(next_move.author.clone());
+            },
+            MoveType::AcceptScore => {
+                let score = state.count_score();
+                state.outcome = if score.black > score.white {
+                    Outcome::Winner(self.black.clone())
+                } else if score.white > score.black {
+                    Outcome::Winner(self.white.clone())
+                } else {
+                    Outcome::Draw
+                };
+                state.score = Some(score);
+            },
+            MoveType::Resume => {
+                state.phase = Phase::Playing;
+                state.consecutive_passes = 0;
+                state.dead_stones = Vec::new();
+                state.marked_by = None;
+                state.to_move = game.next_player(&next_move.author);
+            },
+        }
+        state.history.push(board_key(&state.board));
+        state
+    }
+
+    /// Score the board treating the marked stones as dead
+    pub fn count_score(&self) -> Score {
+        let size = self.settings.board_size;
+        let mut board = self.board.clone();
+        let (mut black_prisoners, mut white_prisoners) = (self.black_captures, self.white_captures);
+        for stone in self.dead_stones.iter() {
+            match board[stone.x][stone.y] {
+                BLACK => white_prisoners += 1,
+                WHITE => black_prisoners += 1,
+                _ => {},
+            }
+            board[stone.x][stone.y] = EMPTY;
+        }
+
+        let (mut black_area, mut white_area) = (0, 0);
+        let mut seen = vec![vec![false; size]; size];
+        for x in 0..size {
+            for y in 0..size {
+                match board[x][y] {
+                    BLACK => black_area += 1,
+                    WHITE => white_area += 1,
+                    _ if !seen[x][y] => {
+                        // an empty region belongs to a player if only their stones border it
+                        let (region, borders) = region(&board, Point{x, y});
+                        region.iter().for_each(|point| seen[point.x][point.y] = true);
+                        if borders == vec![BLACK] {
+                            black_area += region.len();
+                        } else if borders == vec![WHITE] {
+                            white_area += region.len();
+                        }
+                    },
+                    _ => {},
+                }
+            }
+        }
+
+        let (black, white) = match self.settings.scoring {
+            Scoring::Area => (black_area, white_area),
+            Scoring::Territory => {
+                let black_stones = board.iter().flatten().filter(|point| **point == BLACK).count();
+                let white_stones = board.iter().flatten().filter(|point| **point == WHITE).count();
+                (black_area - black_stones + black_prisoners, white_area - white_stones + white_prisoners)
+            },
+        };
+        Score {
+            black: black as f64,
+            white: white as f64 + self.settings.komi,
+        }
+    }
+}
+
+/*========================================
+=            Helper functions            =
+========================================*/
+
+/// A string of the board contents, used to recognise repeated positions
+pub fn board_key(board: &Board) -> String {
+    board.iter().flatten().map(|point| (b'0' + point) as char).collect()
+}
+
+pub fn neighbours(board: &Board, point: Point) -> Vec<Point> {
+    let size = board.len();
+    let mut points = Vec::new();
+    if point.x > 0 { points.push(Point{x: point.x - 1, y: point.y}) }
+    if point.x + 1 < size { points.push(Point{x: point.x + 1, y: point.y}) }
+    if point.y > 0 { points.push(Point{x: point.x, y: point.y - 1}) }
+    if point.y + 1 < size { points.push(Point{x: point.x, y: point.y + 1}) }
+    points
+}
+
+/// The connected points of the same contents as the given point and the contents of the points around them
+fn region(board: &Board, start: Point) -> (Vec<Point>, Vec<u8>) {
+    let contents = board[start.x][start.y];
+    let mut points = vec![start];
+    let mut borders = Vec::new();
+    let mut i = 0;
+    while i < points.len() {
+        for next in neighbours(board, points[i]) {
+            let next_contents = board[next.x][next.y];
+            if next_contents == contents {
+                if !points.contains(&next) {
+                    points.push(next);
+                }
+            } else if !borders.contains(&next_contents) {
+                borders.push(next_contents);
+            }
+        }
+        i += 1;
+    }
+    borders.sort();
+    (points, borders)
+}
+
+/// The stones connected to the stone at the given point and whether the group has any liberties
+pub fn group(board: &Board, stone: Point) -> (Vec<Point>, bool) {
+    let (stones, borders) = region(board, stone);
+    (stones, borders.contains(&EMPTY))
+}
+
+/// Place a stone and remove any opponent groups left without liberties, returning the new board and the number of stones captured.
+/// A group of the player's own stones without liberties (suicide) is left on the board.
+pub fn place_stone(board: &Board, point: Point, colour: u8) -> (Board, usize) {
+    let mut board = board.clone();
+    board[point.x][point.y] = colour;
+    let mut captured = 0;
+    for next in neighbours(&board, point) {
+        let contents = board[next.x][next.y];
+        if contents != EMPTY && contents != colour {
+            let (stones, has_liberties) = group(&board, next);
+            if !has_liberties {
+                captured += stones.len();
+                stones.iter().for_each(|stone| board[stone.x][stone.y] = EMPTY);
+            }
+        }
+    }
+    (board, captured)
+}
+
+/*=====  End of Helper functions  ======*/
@@ -0,0 +1,112 @@
+use hdk::holochain_persistence_api::{
+    cas::content::Address,
+};
+
+use crate::game::Game;
+use crate::game_type::is_players_turn;
+use super::{
+    Go,
+    GameState,
+    moves::Point,
+    MoveType,
+    state::{board_key, group, place_stone, Phase, EMPTY},
+};
+
+
+/**
+ *
+ * Stones are placed and passes made in turn. Once both players pass the game moves on to scoring,
+ * where either player can mark dead stones and the other accepts the score or resumes play.
+ *
+ */
+
+
+impl MoveType {
+    pub fn is_valid(&self, game: Game, game_state: GameState, author: &Address) -> Result<(), String> {
+        match self {
+            MoveType::Place{pos} => {
+                is_playing(&game_state)?;
+                is_players_turn::<Go>(&game, &game_state, author)?;
+                pos.is_in_bounds(&game_state)?;
+                pos.is_empty(&game_state)?;
+
+                let (board, _) = place_stone(&game_state.board, *pos, game_state.colour_of(author));
+                if !group(&board, *pos).1 {
+                    return Err("Suicide is not allowed, the stone would have no liberties.".into())
+                }
+                let key = board_key(&board);
+                let history = &game_state.history;
+                if history.len() >= 2 && history[history.len() - 2] == key {
+                    Err("Ko: the stone cannot be recaptured immediately, play elsewhere first.".into())
+                } else if game_state.settings.superko && history.contains(&key) {
+                    Err("Superko: the move would repeat an earlier board position.".into())
+                } else {
+                    Ok(())
+                }
+            },
+            MoveType::Pass => {
+                is_playing(&game_state)?;
+                is_players_turn::<Go>(&game, &game_state, author)
+            },
+            MoveType::MarkDead{stones} => {
+                is_scoring(&game_state)?;
+                for stone in stones {
+                    stone.is_in_bounds(&game_state)?;
+                    if game_state.board[stone.x][stone.y] == EMPTY {
+                        return Err(format!("There is no stone at ({}, {}) to mark as dead.", stone.x, stone.y))
+                    }
+                }
+                Ok(())
+            },
+            MoveType::AcceptScore => {
+                is_scoring(&game_state)?;
+                match &game_state.marked_by {
+                    None => Err("The dead stones must be marked before the score can be accepted.".into()),
+                    Some(marked_by) if marked_by == author => Err("Your opponent must accept the dead stones you marked.".into()),
+                    Some(_) => Ok(()),
+                }
+            },
+            MoveType::Resume => is_scoring(&game_state),
+        }
+    }
+}
+
+
+/*========================================
+=            Helper functions            =
+========================================*/
+
+fn is_playing(game_state: &GameState) -> Result<(), String> {
+    match game_state.phase {
+        Phase::Playing => Ok(()),
+        Phase::Scoring => Err("Both players have passed. Mark the dead stones, accept the score or resume play.".into()),
+    }
+}
+
+fn is_scoring(game_state: &GameState) -> Result<(), String> {
+    match game_state.phase {
+        Phase::Scoring => Ok(()),
+        Phase::Playing => Err("Scoring only starts once both players pass in a row.".into()),
+    }
+}
+
+impl Point {
+    fn is_in_bounds(&self, game_state: &GameState) -> Result<(), String> {
+        if self.x < game_state.settings.board_size
+        && self.y < game_state.settings.board_size // no need to check > 0 as usize is always positive
+        {
+            Ok(())
+        } else {
+            Err("Position is not in bounds".to_string())
+        }
+    }
+
+    fn is_empty(&self, game_state: &GameState) -> Result<(), String> {
+        match game_state.board[self.x][self.y] == EMPTY {
+            true => Ok(()),
+            false => Err("A stone already exists at that position.".to_string())
+        }
+    }
+}
+
+/*=====  End of Helper functions  ======*/
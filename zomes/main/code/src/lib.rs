@@ -14,7 +14,8 @@ use hdk_proc_macros::zome;
 use hdk::{
     AGENT_ADDRESS,
     entry_definition::ValidatingEntryType,
-    error::ZomeApiResult,
+    error::{ZomeApiResult, ZomeApiError},
+    holochain_json_api::json::JsonString,
     holochain_persistence_api::{
         cas::content::{
             Address,
@@ -26,24 +27,28 @@ use hdk::{
     },
 };
 
-/**
- * DEVCAMP TODO #8:
- * Replace the tictactoe game import with your own game imports
- */
+#[macro_use]
+mod game_type;
 mod tictactoe;
-pub use tictactoe::{
-   GameState,
-   MoveType,
-};
-
-
+mod checkers;
+mod chess;
+mod go;
+mod battleship;
+mod rockpaperscissors;
+mod pig;
+
+mod error;
 mod game;
 mod game_move;
 mod matchmaking;
+mod snapshot;
+mod time_control;
 
-use game::Game;
-use game_move::{Move, MoveInput};
-use matchmaking::{GameProposal, GetResponse};
+use game::{Game, GameIntegrity, Team, TeamOutcome};
+use game_move::{Move, MoveInput, Outcome, Secret};
+use game_type::GameType;
+use time_control::TimeControl;
+use matchmaking::{FirstMover, GameProposal, GetResponse, ProposalFilter, ProposalResponse};
 
 #[zome]
 pub mod main {
@@ -72,6 +77,11 @@ pub mod main {
         game_move::definition()
     }
 
+    #[entry_def]
+    fn snapshot_entry_def() -> ValidatingEntryType {
+        snapshot::definition()
+    }
+
     #[entry_def]
     fn game_proposal_def() -> ValidatingEntryType {
         matchmaking::game_proposal_def()
@@ -90,99 +100,102 @@ pub mod main {
     ======================================*/
 
     #[zome_fn("hc_public")]
-    fn create_game(opponent: Address, timestamp: u32) -> ZomeApiResult<Address> {
-        let new_game = Game {
-            player_1: AGENT_ADDRESS.to_string().into(),
-            player_2: opponent,
-            created_at: timestamp,
-        };
-        let game_entry = Entry::App(
-            "game".into(),
-            new_game.into(),
-        );
-        hdk::commit_entry(&game_entry)
+    fn create_game(opponents: Vec<Address>, timestamp: u32, game_type: GameType, settings: Option<serde_json::Value>, time_control: Option<TimeControl>) -> ZomeApiResult<Address> {
+        game::create_game(opponents, timestamp, game_type, settings.unwrap_or(serde_json::Value::Null), time_control)
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_team_game(teams: Vec<Team>, timestamp: u32, game_type: GameType, settings: Option<serde_json::Value>, time_control: Option<TimeControl>) -> ZomeApiResult<Address> {
+        game::create_team_game(teams, timestamp, game_type, settings.unwrap_or(serde_json::Value::Null), time_control)
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_game_from_pgn(opponent: Address, timestamp: u32, pgn: String) -> ZomeApiResult<Address> {
+        let settings = chess::pgn::parse_pgn(&pgn).map_err(ZomeApiError::ValidationFailed)?;
+        game::create_game(vec![opponent], timestamp, GameType::Chess, serde_json::to_value(settings).map_err(|e| ZomeApiError::Internal(e.to_string()))?, None)
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_game_from_fen(opponent: Address, timestamp: u32, fen: String) -> ZomeApiResult<Address> {
+        let settings = chess::Settings{fen: Some(fen), opening: Vec::new()};
+        settings.is_valid().map_err(ZomeApiError::ValidationFailed)?;
+        game::create_game(vec![opponent], timestamp, GameType::Chess, serde_json::to_value(settings).map_err(|e| ZomeApiError::Internal(e.to_string()))?, None)
     }
 
     #[zome_fn("hc_public")]
     fn make_move(new_move: MoveInput) -> ZomeApiResult<()> {
-        // get all the moves from the DHT by following the hash chain
-        let published_moves = game::get_moves(&new_move.game)?;
-
-        // get all moves in this agents local chain
-        let chain_moves = hdk::query("move".into(), 0, 0)?;
-
-        // Update this agents local chain to match the game state
-        let base_address = match published_moves.clone().last() {
-            Some(last_move) => { // add any moves NOT found in the DHT to this agents local chain
-                for _move in &published_moves {
-                    let move_entry = Entry::App("move".into(), _move.into());
-                    if !chain_moves.contains(&move_entry.address()) {
-                        hdk::commit_entry(&move_entry)?;
-                    }
-                }
-                Entry::App("move".into(), last_move.into()).address()
-
-            }
-            None => { // no moves have been made so commit the Game to local chain
-                let game = game::get_game(&new_move.game)?;
-                let game_entry = Entry::App("game".into(), game.into());
-                hdk::commit_entry(&game_entry)?
-            }
-        };
-
-        let new_move = Move {
-            game: new_move.game,
-            author: AGENT_ADDRESS.to_string().into(),
-            move_type: new_move.move_type,
-            previous_move: base_address.clone(),
-            timestamp: new_move.timestamp,
-        };
-        let move_entry = Entry::App(
-            "move".into(),
-            new_move.into(),
-        );
-        let move_address = hdk::commit_entry(&move_entry)?;
-
-        match published_moves.last() {
-            Some(_) => {
-                // base is a move
-                hdk::link_entries(&base_address, &move_address, "move->move", "")?;
-            }
-            None => {
-                // base is a game
-                hdk::link_entries(&base_address, &move_address, "game->move", "")?;
-            }
-        }
+        game::make_move(new_move)
+    }
 
-        Ok(())
+    #[zome_fn("hc_public")]
+    fn get_moves(game_address: Address, from: Option<u32>, limit: Option<u32>) -> ZomeApiResult<Vec<Move>> {
+        match (from, limit) {
+            (None, None) => game::get_moves(&game_address),
+            (from, limit) => game::get_moves_page(&game_address, from.unwrap_or(0), limit.unwrap_or(game::MAX_PAGE_SIZE)),
+        }
     }
 
     #[zome_fn("hc_public")]
-    fn get_game_hash(opponent: Address, timestamp: u32) -> ZomeApiResult<Address> {
-        let new_game = Game {
-            player_1: opponent,
-            player_2: AGENT_ADDRESS.to_string().into(),
-            created_at: timestamp,
-        };
+    fn get_game_hash(game: Game) -> ZomeApiResult<Address> {
         Ok(Entry::App(
             "game".into(),
-            new_game.into(),
+            game.into(),
         ).address())
     }
 
     #[zome_fn("hc_public")]
-    fn get_state(game_address: Address) -> ZomeApiResult<GameState> {
+    fn hash_secret(key: String, data: serde_json::Value, salt: String) -> ZomeApiResult<Address> {
+        Ok(Secret{key, data, salt}.commitment())
+    }
+
+    #[zome_fn("hc_public")]
+    fn hash_move(game_address: Address, move_type: serde_json::Value, salt: String) -> ZomeApiResult<Address> {
+        game::hash_move(&game_address, move_type, salt)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_state(game_address: Address) -> ZomeApiResult<JsonString> {
         game::get_state(&game_address)
     }
 
     #[zome_fn("hc_public")]
     fn render_state(game_address: Address) -> ZomeApiResult<String> {
-        Ok(game::get_state(&game_address)?.render())
+        game::render_state(&game_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_result(game_address: Address) -> ZomeApiResult<Outcome> {
+        game::get_result(&game_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_team_results(game_address: Address) -> ZomeApiResult<Vec<TeamOutcome>> {
+        game::get_team_results(&game_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_game_integrity(game_address: Address) -> ZomeApiResult<GameIntegrity> {
+        game::get_game_integrity(&game_address)
     }
 
     #[zome_fn("hc_public")]
-    fn get_valid_moves() -> ZomeApiResult<Vec<MoveType>> {
-        Ok(MoveType::describe())
+    fn get_pgn(game_address: Address) -> ZomeApiResult<String> {
+        game::get_pgn(&game_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_sgf(game_address: Address) -> ZomeApiResult<String> {
+        game::get_sgf(&game_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_valid_moves(game_address: Address) -> ZomeApiResult<JsonString> {
+        game::get_valid_moves(&game_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_game_types() -> ZomeApiResult<Vec<GameType>> {
+        Ok(GameType::all())
     }
 
     #[zome_fn("hc_public")]
@@ -191,25 +204,38 @@ pub mod main {
     }
 
     #[zome_fn("hc_public")]
-    fn create_proposal(message: String) -> ZomeApiResult<Address> {
-        matchmaking::handle_create_proposal(message)
+    fn create_proposal(message: String, game_type: Option<GameType>, settings: Option<serde_json::Value>, time_control: Option<TimeControl>, rated: Option<bool>, first_mover: Option<FirstMover>, expires_at: Option<u32>) -> ZomeApiResult<Address> {
+        matchmaking::handle_create_proposal(
+            message,
+            game_type.unwrap_or(GameType::TicTacToe),
+            settings.unwrap_or(serde_json::Value::Null),
+            time_control,
+            rated.unwrap_or(false),
+            first_mover,
+            expires_at,
+        )
     }
 
     #[zome_fn("hc_public")]
-    fn get_proposals() -> ZomeApiResult<Vec<GetResponse<GameProposal>>> {
-        matchmaking::handle_get_proposals()
+    fn get_proposals(filter: Option<ProposalFilter>, now: u32) -> ZomeApiResult<Vec<GetResponse<GameProposal>>> {
+        matchmaking::handle_get_proposals(filter.unwrap_or_default(), now)
     }
 
     #[zome_fn("hc_public")]
-    fn accept_proposal(proposal_addr: Address, created_at: u32) -> ZomeApiResult<Address> {
-        matchmaking::handle_accept_proposal(proposal_addr, created_at)
+    fn accept_proposal(proposal_addr: Address, created_at: u32, other_players: Option<Vec<Address>>) -> ZomeApiResult<Address> {
+        matchmaking::handle_accept_proposal(proposal_addr, created_at, other_players.unwrap_or_default())
     }
 
     #[zome_fn("hc_public")]
-    fn check_responses(proposal_addr: Address) -> ZomeApiResult<Vec<GetResponse<Game>>> {
+    fn check_responses(proposal_addr: Address) -> ZomeApiResult<Vec<ProposalResponse>> {
         matchmaking::handle_check_responses(proposal_addr)
     }
 
+    #[zome_fn("hc_public")]
+    fn confirm_response(proposal_addr: Address, game_addr: Address) -> ZomeApiResult<Address> {
+        matchmaking::handle_confirm_response(proposal_addr, game_addr)
+    }
+
     #[zome_fn("hc_public")]
     fn remove_proposal(proposal_addr: Address) -> ZomeApiResult<Address> {
         matchmaking::handle_remove_proposal(proposal_addr)
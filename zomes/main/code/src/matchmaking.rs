@@ -1,7 +1,8 @@
+use std::convert::TryFrom;
 use hdk::{
     AGENT_ADDRESS,
     entry_definition::ValidatingEntryType,
-    error::ZomeApiResult,
+    error::{ZomeApiResult, ZomeApiError},
     holochain_persistence_api::{
         cas::content::{AddressableContent, Address},
     },
@@ -10,7 +11,7 @@ use hdk::{
     },
     holochain_core_types::{
         dna::entry_types::Sharing,
-        validation::EntryValidationData,
+        validation::{EntryValidationData, LinkValidationData},
         entry::Entry,
         link::LinkMatch,
     }
@@ -19,12 +20,85 @@ use hdk::{
 use serde::Serialize;
 use std::fmt::Debug;
 
-use crate::game::Game;
+use crate::game::{self, Game};
+use crate::game_move::{MoveInput, MoveType, MetaMove};
+use crate::game_type::GameType;
+use crate::time_control::{TimeControl, unix_seconds};
 
+/**
+ *
+ * A proposal says what game is on offer. Its game type, settings, time control and whether it is rated
+ * are copied into the game created in response, and the proposer can ask to move first or second.
+ * A proposal can expire, after which it is no longer listed and no game can be created from it.
+ *
+ */
 #[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
 pub struct GameProposal {
     pub agent: Address,
     pub message: String,
+    pub game_type: GameType,
+    pub settings: serde_json::Value,
+    pub time_control: Option<TimeControl>,
+    pub rated: bool,
+    pub first_mover: Option<FirstMover>,
+    /// The unix time in seconds from which the proposal can no longer be accepted
+    #[serde(default)]
+    pub expires_at: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum FirstMover {
+    Proposer,
+    Acceptor,
+}
+
+impl GameProposal {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.map_or(false, |expires_at| now >= i64::from(expires_at))
+    }
+
+    /// The agent who accepted the proposal with the game. `handle_accept_proposal` seats them next to the proposer
+    pub fn acceptor<'a>(&self, game: &'a Game) -> Option<&'a Address> {
+        game.players.iter().take(2).find(|player| *player != &self.agent)
+    }
+
+    /// Ok(()) if the game was created on the terms of the proposal
+    pub fn is_offered(&self, game: &Game) -> Result<(), String> {
+        if game.game_type != self.game_type || game.settings != self.settings {
+            Err("The game must be of the type and settings that were proposed".into())
+        } else if game.time_control != self.time_control || game.rated != self.rated {
+            Err("The game must have the time control and rating that were proposed".into())
+        } else if !game.players.contains(&self.agent) {
+            Err("The author of the proposal must be one of the players".into())
+        } else {
+            match self.first_mover {
+                Some(FirstMover::Proposer) if game.first_mover() != self.agent => Err("The author of the proposal asked to move first".into()),
+                Some(FirstMover::Acceptor) if game.first_mover() == self.agent => Err("The author of the proposal asked to move second".into()),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+/// Proposals can be looked up by any of their terms, a term that is not given matches every proposal
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone, Default)]
+#[serde(default)]
+pub struct ProposalFilter {
+    pub game_type: Option<GameType>,
+    pub settings: Option<serde_json::Value>,
+    pub time_control: Option<TimeControl>,
+    pub rated: Option<bool>,
+    pub first_mover: Option<FirstMover>,
+}
+
+impl ProposalFilter {
+    pub fn matches(&self, proposal: &GameProposal) -> bool {
+        self.game_type.as_ref().map_or(true, |game_type| game_type == &proposal.game_type)
+            && self.settings.as_ref().map_or(true, |settings| settings == &proposal.settings)
+            && self.time_control.as_ref().map_or(true, |time_control| Some(time_control) == proposal.time_control.as_ref())
+            && self.rated.map_or(true, |rated| rated == proposal.rated)
+            && self.first_mover.as_ref().map_or(true, |first_mover| Some(first_mover) == proposal.first_mover.as_ref())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -33,18 +107,48 @@ pub struct GetResponse<T> {
     pub address: Address
 }
 
+/// A game created in response to a proposal. It stays pending until the proposer confirms one of the
+/// responses, after which the others are rejected.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ProposalResponse {
+    pub entry: Game,
+    pub address: Address,
+    pub status: ResponseStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ResponseStatus {
+    Pending,
+    Confirmed,
+    Rejected,
+}
+
 impl<T: Into<JsonString> + Debug + Serialize> From<GetResponse<T>> for JsonString {
     fn from(u: GetResponse<T>) -> JsonString {
         default_to_json(u)
     }
 } 
 
-pub fn handle_create_proposal(message: String) -> ZomeApiResult<Address> {
+pub fn handle_create_proposal(
+    message: String,
+    game_type: GameType,
+    settings: serde_json::Value,
+    time_control: Option<TimeControl>,
+    rated: bool,
+    first_mover: Option<FirstMover>,
+    expires_at: Option<u32>,
+) -> ZomeApiResult<Address> {
 
     // create the data as a struct
     let game_proposal_data = GameProposal { 
         agent: AGENT_ADDRESS.to_string().into(),
         message,
+        game_type,
+        settings,
+        time_control,
+        rated,
+        first_mover,
+        expires_at,
     };
     
     // create an entry
@@ -76,42 +180,84 @@ pub fn handle_create_proposal(message: String) -> ZomeApiResult<Address> {
     Ok(proposal_address)
 }
 
-pub fn handle_get_proposals() -> ZomeApiResult<Vec<GetResponse<GameProposal>>> {
+/// Lists the open proposals, leaving out the ones that have expired by `now`, the unix time in seconds. The zome
+/// has no clock of its own so the time is the caller's word, which is why expiry is also enforced on accept.
+pub fn handle_get_proposals(filter: ProposalFilter, now: u32) -> ZomeApiResult<Vec<GetResponse<GameProposal>>> {
     // define the anchor entry again and compute its hash
     let anchor_address = Entry::App(
         "anchor".into(),
         "game_proposals".into()
     ).address();
-    
-    Ok(
-        hdk::utils::get_links_and_load_type(
-            &anchor_address, 
-            LinkMatch::Exactly("has_proposal"), // the link type to match
-            LinkMatch::Any
-        )?.into_iter().map(|proposal: GameProposal| {
-            let address = Entry::App("game_proposal".into(), proposal.clone().into()).address();
-            GetResponse{entry: proposal, address}
-        }).collect()
-    )
+
+    // removed proposals can no longer be loaded, expired ones are hidden and proposals with a confirmed game are closed
+    let mut open_proposals = Vec::new();
+    for entry in hdk::get_links_and_load(&anchor_address, LinkMatch::Exactly("has_proposal"), LinkMatch::Any)? {
+        let proposal = match entry {
+            Ok(Entry::App(_, entry_data)) => match GameProposal::try_from(entry_data) {
+                Ok(proposal) => proposal,
+                Err(_) => continue,
+            },
+            _ => continue,
+        };
+        if !filter.matches(&proposal) || proposal.is_expired(i64::from(now)) {
+            continue
+        }
+        let address = Entry::App("game_proposal".into(), proposal.clone().into()).address();
+        if !is_closed(&address)? {
+            open_proposals.push(GetResponse{entry: proposal, address});
+        }
+    }
+    Ok(open_proposals)
+}
+
+/// A proposal is closed once its author has confirmed one of the games created in response
+fn is_closed(proposal_addr: &Address) -> ZomeApiResult<bool> {
+    Ok(confirmed_game(proposal_addr)?.is_some())
 }
 
-pub fn handle_accept_proposal(proposal_addr: Address, created_at: u32) -> ZomeApiResult<Address> {
+pub fn confirmed_game(proposal_addr: &Address) -> ZomeApiResult<Option<Address>> {
+    Ok(hdk::get_links(proposal_addr, LinkMatch::Exactly("confirmed"), LinkMatch::Any)?.addresses().into_iter().next())
+}
+
+pub fn handle_accept_proposal(proposal_addr: Address, created_at: u32, other_players: Vec<Address>) -> ZomeApiResult<Address> {
     // this will early return error if it doesn't exist
     let proposal: GameProposal = hdk::utils::get_as_type(proposal_addr.clone())?;
+    if is_closed(&proposal_addr)? {
+        return Err(ZomeApiError::ValidationFailed("The author of this proposal has already confirmed a game".into()))
+    }
+    if proposal.is_expired(i64::from(created_at)) {
+        return Err(ZomeApiError::ValidationFailed("This proposal has expired".into()))
+    }
 
-    // create the new game
-    let game = Game {
-        player_1: AGENT_ADDRESS.to_string().into(),
-        player_2: proposal.agent,
+    // create the new game on the terms of the proposal. By default the proposer takes the seat of the
+    // first player, any other players follow in the order given
+    let me: Address = AGENT_ADDRESS.to_string().into();
+    let mut players = vec![me, proposal.agent.clone()];
+    players.extend(other_players);
+    let mut game = Game {
+        game_type: proposal.game_type,
+        settings: proposal.settings,
+        players,
+        teams: Vec::new(),
+        time_control: proposal.time_control,
+        rated: proposal.rated,
+        proposal: Some(proposal_addr.clone()),
         created_at,
     };
+    // which seat moves first is up to the rules, e.g. a chess position with black to move
+    let proposer_moves_first = game.first_mover() == proposal.agent;
+    match proposal.first_mover {
+        Some(FirstMover::Proposer) if !proposer_moves_first => game.players.swap(0, 1),
+        Some(FirstMover::Acceptor) if proposer_moves_first => game.players.swap(0, 1),
+        _ => {},
+    }
     let game_entry = Entry::App(
         "game".into(),
         game.into()
     );
     let game_addr = hdk::commit_entry(&game_entry)?;
 
-    // link to the proposal
+    // link to the proposal, the game is pending until the proposer confirms it
     hdk::link_entries(
         &proposal_addr,
         &game_addr,
@@ -121,17 +267,54 @@ pub fn handle_accept_proposal(proposal_addr: Address, created_at: u32) -> ZomeAp
     Ok(game_addr)
 }
 
-pub fn handle_check_responses(proposal_addr: Address) -> ZomeApiResult<Vec<GetResponse<Game>>> {
+pub fn handle_check_responses(proposal_addr: Address) -> ZomeApiResult<Vec<ProposalResponse>> {
+    let confirmed = confirmed_game(&proposal_addr)?;
     Ok(
-        hdk::utils::get_links_and_load_type(&proposal_addr, LinkMatch::Exactly("from_proposal".into()), LinkMatch::Any)?
+        hdk::utils::get_links_and_load_type(&proposal_addr, LinkMatch::Exactly("from_proposal"), LinkMatch::Any)?
         .into_iter().map(|game: Game| {
             let address = Entry::App("game".into(), game.clone().into()).address();
-            GetResponse{entry: game, address}
+            let status = match &confirmed {
+                None => ResponseStatus::Pending,
+                Some(confirmed) if confirmed == &address => ResponseStatus::Confirmed,
+                Some(_) => ResponseStatus::Rejected,
+            };
+            ProposalResponse{entry: game, address, status}
         }).collect()
     )
 }
 
+/// The author of a proposal picks which of the games created in response they will play
+pub fn handle_confirm_response(proposal_addr: Address, game_addr: Address) -> ZomeApiResult<Address> {
+    let proposal: GameProposal = hdk::utils::get_as_type(proposal_addr.clone())?;
+    if proposal.agent != Address::from(AGENT_ADDRESS.to_string()) {
+        return Err(ZomeApiError::ValidationFailed("Only the author of a proposal can confirm a response".into()))
+    }
+    if is_closed(&proposal_addr)? {
+        return Err(ZomeApiError::ValidationFailed("A response to this proposal has already been confirmed".into()))
+    }
+    hdk::link_entries(
+        &proposal_addr,
+        &game_addr,
+        "confirmed",
+        ""
+    )?;
+    // the game starts with the Confirm, which validation checks against the link just made
+    let game: Game = hdk::utils::get_as_type(game_addr.clone())?;
+    game::make_move(MoveInput {
+        game: game_addr.clone(),
+        move_type: MoveType::Meta(MetaMove::Confirm),
+        timestamp: game.created_at,
+    })?;
+    Ok(game_addr)
+}
+
 pub fn handle_remove_proposal(proposal_addr: Address) -> ZomeApiResult<Address> {
+    let anchor_address = Entry::App(
+        "anchor".into(),
+        "game_proposals".into()
+    ).address();
+    // take it out of the lobby before removing it, the link can only be removed while the proposal exists
+    hdk::remove_link(&anchor_address, &proposal_addr, "has_proposal", "")?;
     hdk::remove_entry(&proposal_addr)
 }
 
@@ -151,16 +334,27 @@ pub fn game_proposal_def() -> ValidatingEntryType {
             match validation_data {
                 // only match if the entry is being created (not modified or deleted)
                 EntryValidationData::Create{ entry, validation_data } => {
-                    let game_proposal = GameProposal::from(entry);
-                    if validation_data.sources().contains(&game_proposal.agent) {
-                        Ok(())
-                    } else {
-                        Err("Cannot author a proposal from another agent".into())
+                    let game_proposal = entry;
+                    if !validation_data.sources().contains(&game_proposal.agent) {
+                        return Err("Cannot author a proposal from another agent".into())
+                    }
+                    let header_time = unix_seconds(&validation_data.package.chain_header.timestamp().to_string())
+                        .ok_or("Could not read the time of the proposal's header")?;
+                    if game_proposal.is_expired(header_time) {
+                        return Err("A proposal cannot expire before it is made".into())
+                    }
+                    match &game_proposal.time_control {
+                        Some(time_control) => time_control.is_valid(),
+                        None => Ok(()),
                     }
                     
                 },
-                EntryValidationData::Delete{..} => { // should update to only the author can delete
-                    Ok(())
+                EntryValidationData::Delete{old_entry, validation_data, ..} => {
+                    if validation_data.sources().contains(&old_entry.agent) {
+                        Ok(())
+                    } else {
+                        Err("Only the author of a proposal can remove it".into())
+                    }
                 },
                 _ => {
                     Err("Cannot modify, only create and delete".into())
@@ -172,8 +366,75 @@ pub fn game_proposal_def() -> ValidatingEntryType {
                 "game",
                 link_type: "from_proposal",
                 validation_package: || { hdk::ValidationPackageDefinition::Entry },
-                validation: | _validation_data: hdk::LinkValidationData| {
-                    Ok(())
+                validation: | validation_data: hdk::LinkValidationData| {
+                    match validation_data {
+                        LinkValidationData::LinkAdd{link, validation_data} => {
+                            let link = link.link();
+                            let proposal: GameProposal = hdk::utils::get_as_type(link.base().clone())?;
+                            let game: Game = hdk::utils::get_as_type(link.target().clone())?;
+                            if game.proposal.as_ref() != Some(link.base()) {
+                                return Err("The game was not created from this proposal".into())
+                            }
+                            // both the time the acceptor gives and the time the link was made must be before the expiry
+                            let header_time = unix_seconds(&validation_data.package.chain_header.timestamp().to_string())
+                                .ok_or("Could not read the time of the link's header")?;
+                            if proposal.is_expired(header_time) || proposal.is_expired(i64::from(game.created_at)) {
+                                return Err("An expired proposal cannot be accepted".into())
+                            }
+                            proposal.is_offered(&game)
+                        },
+                        LinkValidationData::LinkRemove{link, validation_data} => {
+                            // only the proposer or the agent who accepted with the game can withdraw it
+                            let link = link.link();
+                            let proposal: GameProposal = hdk::utils::get_as_type(link.base().clone())?;
+                            let game: Game = hdk::utils::get_as_type(link.target().clone())?;
+                            let sources = validation_data.sources();
+                            if sources.contains(&proposal.agent) || proposal.acceptor(&game).map_or(false, |acceptor| sources.contains(acceptor)) {
+                                Ok(())
+                            } else {
+                                Err("Only the author of a proposal or the agent who accepted it can remove a response".into())
+                            }
+                        },
+                    }
+                }
+            ),
+            to!(
+                "game",
+                link_type: "confirmed",
+                validation_package: || { hdk::ValidationPackageDefinition::ChainFull },
+                validation: | validation_data: hdk::LinkValidationData| {
+                    match validation_data {
+                        LinkValidationData::LinkAdd{link, validation_data} => {
+                            let link = link.link();
+                            let proposal: GameProposal = hdk::utils::get_as_type(link.base().clone())?;
+                            if !validation_data.sources().contains(&proposal.agent) {
+                                return Err("Only the author of a proposal can confirm a response".into())
+                            }
+                            let game: Game = hdk::utils::get_as_type(link.target().clone())?;
+                            if game.proposal.as_ref() != Some(link.base()) {
+                                return Err("Only a game created in response to the proposal can be confirmed".into())
+                            }
+                            proposal.is_offered(&game)?;
+                            // the links on the proposal differ between validators, but any other confirmation
+                            // was made by the proposer and so is in the chain they hand in
+                            let local_chain = validation_data.package.source_chain_entries
+                                .ok_or("Could not retrieve source chain")?;
+                            let confirmed_before = local_chain.iter().any(|entry| match entry {
+                                Entry::LinkAdd(link_data) => {
+                                    let other = link_data.link();
+                                    other.link_type() == link.link_type() && other.base() == link.base() && other.target() != link.target()
+                                },
+                                _ => false,
+                            });
+                            if confirmed_before {
+                                return Err("A response to this proposal has already been confirmed".into())
+                            }
+                            Ok(())
+                        },
+                        LinkValidationData::LinkRemove{..} => {
+                            Err("A confirmed response can't be withdrawn".into())
+                        },
+                    }
                 }
             )
         ]
@@ -196,8 +457,21 @@ pub fn anchor_def() -> ValidatingEntryType {
                 validation_package: || {
                     hdk::ValidationPackageDefinition::Entry
                 },
-                validation: | _validation_data: hdk::LinkValidationData| {
-                    Ok(())
+                validation: | validation_data: hdk::LinkValidationData| {
+                    match validation_data {
+                        LinkValidationData::LinkRemove{link, validation_data} => {
+                            // only the author can take their proposal out of the lobby
+                            let proposal: GameProposal = hdk::utils::get_as_type(link.link().target().clone())?;
+                            if validation_data.sources().contains(&proposal.agent) {
+                                Ok(())
+                            } else {
+                                Err("Only the author of a proposal can remove it".into())
+                            }
+                        },
+                        LinkValidationData::LinkAdd{..} => {
+                            Ok(())
+                        },
+                    }
                 }
             )
         ]
@@ -0,0 +1,76 @@
+/**
+ * All of this code is specific to the dice game Pig
+ * By changing the moves, state, reducer and validation rules you can implement you own game.
+ */
+
+pub mod state;
+pub mod validation;
+pub mod moves;
+
+pub use self::{
+    state::{
+        GameState,
+        Settings,
+    },
+    moves::{
+        MoveType,
+    },
+};
+
+use hdk::holochain_persistence_api::cas::content::Address;
+
+use crate::game::Game;
+use crate::game_move::{Move, Outcome, Randomness};
+use crate::game_type::GameRules;
+
+pub struct Pig;
+
+impl GameRules for Pig {
+    type State = GameState;
+    type MoveType = MoveType;
+
+    fn initial(game: &Game) -> GameState {
+        GameState::initial(game)
+    }
+
+    /// Any number of players can take turns rolling, though the game gets long with many
+    fn player_count() -> (usize, usize) {
+        (2, 6)
+    }
+
+    fn evolve(game: &Game, state: &GameState, next_move: &Move, move_type: &MoveType) -> Result<GameState, String> {
+        state.evolve(game, next_move, move_type)
+    }
+
+    fn is_valid(game: &Game, state: &GameState, next_move: &Move, move_type: &MoveType) -> Result<(), String> {
+        move_type.is_valid(game.clone(), state.clone(), &next_move.author)
+    }
+
+    fn render(_game: &Game, state: &GameState) -> String {
+        state.render()
+    }
+
+    fn validate_game(game: &Game) -> Result<(), String> {
+        game.try_settings::<Settings>()?.is_valid()
+    }
+
+    fn outcome(_game: &Game, state: &GameState) -> Outcome {
+        state.outcome()
+    }
+
+    fn request_randomness(_game: &Game, state: &GameState, author: &Address, key: &str) -> Result<GameState, String> {
+        state.request_roll(author, key)
+    }
+
+    fn randomness(game: &Game, state: &GameState, randomness: &Randomness) -> Result<GameState, String> {
+        state.roll(game, randomness)
+    }
+
+    fn to_move(_game: &Game, state: &GameState) -> Option<Address> {
+        Some(state.to_move.clone())
+    }
+
+    fn describe() -> Vec<MoveType> {
+        MoveType::describe()
+    }
+}
@@ -0,0 +1,30 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+
+
+/**
+ *
+ * The MoveType enum defines all the types of moves that are valid in your game and the
+ * data they carry. In Pig the player to move rolls a die as often as they like, adding each roll
+ * to their turn total, and Holds to bank the total. Rolling a 1 loses the turn total and the turn.
+ *
+ * A roll isn't a move of its own. The player to move starts one with
+ * {"RandomnessRequest": {"key": <a key not used before>, "commitment": <hash_secret of their seed>}},
+ * every other player answers with a RandomnessRequest under the same key and once all of them have
+ * committed they reveal their seeds with RandomnessReveal. The die is rolled from the combined seeds.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub enum MoveType {
+    Hold,
+}
+
+impl MoveType {
+	pub fn describe() -> Vec<MoveType> {
+		vec![
+			MoveType::Hold,
+		]
+	}
+}
@@ -0,0 +1,156 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+use hdk::holochain_persistence_api::cas::content::Address;
+use hdk::AGENT_ADDRESS;
+
+use crate::game_move::{Move, Outcome, Randomness};
+use crate::game::Game;
+use super::MoveType;
+
+pub const MAX_TARGET: u32 = 1000;
+
+/**
+ *
+ * The score needed to win is chosen when the game is created, e.g. {"target": 50}.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub target: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            target: 100,
+        }
+    }
+}
+
+impl Settings {
+    pub fn is_valid(&self) -> Result<(), String> {
+        if self.target == 0 || self.target > MAX_TARGET {
+            Err(format!("The target score must be between 1 and {}", MAX_TARGET))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/**
+ *
+ * The state keeps the moves made so far and the banked score of each player, in the order of the players
+ * in the game, the total of the turn being played and the roll that is waiting on the players' seeds.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct GameState {
+    pub settings: Settings,
+    pub moves: Vec<Move>,
+    pub players: Vec<Address>,
+    pub scores: Vec<u32>,
+    pub turn_total: u32,
+    pub to_move: Address,
+    /// The key of the roll the players are committing to, if one has been started
+    pub rolling: Option<String>,
+    pub last_roll: Option<u32>,
+}
+
+impl GameState {
+    pub fn initial(game: &Game) -> Self {
+        GameState {
+            settings: game.settings(),
+            moves: Vec::new(),
+            players: game.players.clone(),
+            scores: vec![0; game.players.len()],
+            turn_total: 0,
+            to_move: game.first_player(),
+            rolling: None,
+            last_roll: None,
+        }
+    }
+
+    pub fn score(&self, player: &Address) -> u32 {
+        self.players.iter().position(|p| p == player).map_or(0, |i| self.scores[i])
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        match self.players.iter().zip(self.scores.iter()).find(|(_, score)| **score >= self.settings.target) {
+            Some((winner, _)) => Outcome::Winner(winner.clone()),
+            None => Outcome::InProgress,
+        }
+    }
+
+    /// Only the player to move can start a roll, the other players join it under the same key
+    pub fn request_roll(&self, author: &Address, key: &str) -> Result<Self, String> {
+        match &self.rolling {
+            None if author != &self.to_move => Err("Only the player to move can roll the die.".into()),
+            None => Ok(GameState {
+                rolling: Some(key.to_string()),
+                ..self.clone()
+            }),
+            Some(rolling) if rolling != key => Err(format!("Commit to a seed for the roll {} first.", rolling)),
+            Some(_) => Ok(self.clone()),
+        }
+    }
+
+    /// Apply a die rolled from the seeds of every player. A 1 loses the turn total and passes the turn.
+    pub fn roll(&self, game: &Game, randomness: &Randomness) -> Result<Self, String> {
+        if self.rolling.as_ref() != Some(&randomness.key) {
+            return Err(format!("{} is not the roll being made.", randomness.key))
+        }
+        let die = randomness.dice(1, 6)[0];
+        let (turn_total, to_move) = if die == 1 {
+            (0, game.next_player(&self.to_move))
+        } else {
+            (self.turn_total + die, self.to_move.clone())
+        };
+        Ok(GameState {
+            turn_total,
+            to_move,
+            rolling: None,
+            last_roll: Some(die),
+            ..self.clone()
+        })
+    }
+
+    pub fn evolve(&self, game: &Game, next_move: &Move, move_type: &MoveType) -> Result<Self, String> {
+        let mut moves = self.moves.clone();
+        moves.push(next_move.to_owned());
+        match move_type {
+            MoveType::Hold => {
+                let index = self.players.iter().position(|player| player == &next_move.author)
+                    .ok_or("Player is not part of this game!")?;
+                let mut scores = self.scores.clone();
+                scores[index] += self.turn_total;
+                Ok(GameState {
+                    moves,
+                    scores,
+                    turn_total: 0,
+                    to_move: game.next_player(&next_move.author),
+                    last_roll: None,
+                    ..self.clone()
+                })
+            },
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let me: Address = AGENT_ADDRESS.to_string().into();
+        let mut disp = format!("\nFirst to {} wins\n\n", self.settings.target);
+        for (i, (player, score)) in self.players.iter().zip(self.scores.iter()).enumerate() {
+            let name = if player == &me { "you".to_string() } else { format!("player {}", i + 1) };
+            let turn = if player == &self.to_move { format!(" <- to move, {} this turn", self.turn_total) } else { String::new() };
+            disp.push_str(&format!("{:>10}: {}{}\n", name, score, turn));
+        }
+        match (self.last_roll, &self.rolling) {
+            (_, Some(key)) => disp.push_str(&format!("\nRolling {}, waiting for every player's seed\n", key)),
+            (Some(1), None) => disp.push_str("\nA 1 was rolled, the turn is lost\n"),
+            (Some(die), None) => disp.push_str(&format!("\nRolled a {}\n", die)),
+            (None, None) => {},
+        }
+        disp
+    }
+}
@@ -0,0 +1,37 @@
+use hdk::holochain_persistence_api::{
+    cas::content::Address,
+};
+
+use crate::game::Game;
+use crate::game_type::is_players_turn;
+use super::{
+    Pig,
+    GameState,
+    MoveType,
+};
+
+
+/**
+ *
+ * Holding is the only move a player makes themselves, the rolls are checked by the framework and
+ * `GameState::request_roll` as they are committed to and revealed.
+ *
+ */
+
+
+impl MoveType {
+    pub fn is_valid(&self, game: Game, game_state: GameState, author: &Address) -> Result<(), String> {
+        match self {
+            MoveType::Hold => {
+                is_players_turn::<Pig>(&game, &game_state, author)?;
+                if let Some(key) = &game_state.rolling {
+                    return Err(format!("Wait for the roll {} before holding.", key))
+                }
+                if game_state.turn_total == 0 {
+                    return Err("Roll the die at least once before holding.".into())
+                }
+                Ok(())
+            },
+        }
+    }
+}
@@ -0,0 +1,66 @@
+/**
+ * All of this code is specific to the game Rock-paper-scissors
+ * By changing the moves, state, reducer and validation rules you can implement you own game.
+ */
+
+pub mod state;
+pub mod validation;
+pub mod moves;
+
+pub use self::{
+    state::{
+        GameState,
+        Settings,
+    },
+    moves::{
+        MoveType,
+    },
+};
+
+use crate::game::Game;
+use crate::game_move::{Move, Outcome};
+use crate::game_type::GameRules;
+
+pub struct RockPaperScissors;
+
+impl GameRules for RockPaperScissors {
+    type State = GameState;
+    type MoveType = MoveType;
+
+    fn initial(game: &Game) -> GameState {
+        GameState::initial(game)
+    }
+
+    fn evolve(_game: &Game, state: &GameState, _next_move: &Move, _move_type: &MoveType) -> Result<GameState, String> {
+        // moves are only ever applied a whole round at a time, see `evolve_round`
+        Ok(state.clone())
+    }
+
+    fn is_valid(game: &Game, state: &GameState, next_move: &Move, move_type: &MoveType) -> Result<(), String> {
+        move_type.is_valid(game.clone(), state.clone(), &next_move.author)
+    }
+
+    fn render(_game: &Game, state: &GameState) -> String {
+        state.render()
+    }
+
+    fn validate_game(game: &Game) -> Result<(), String> {
+        game.try_settings::<Settings>()?.is_valid()
+    }
+
+    fn outcome(_game: &Game, state: &GameState) -> Outcome {
+        state.outcome()
+    }
+
+    fn simultaneous(_game: &Game) -> bool {
+        true
+    }
+
+    fn evolve_round(_game: &Game, state: &GameState, moves: &[(Move, MoveType)]) -> Result<GameState, String> {
+        Ok(state.evolve_round(moves))
+    }
+
+    fn describe() -> Vec<MoveType> {
+        MoveType::describe()
+    }
+}
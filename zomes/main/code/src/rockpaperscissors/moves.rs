@@ -0,0 +1,41 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+
+
+/**
+ *
+ * The MoveType enum defines all the types of moves that are valid in your game and the
+ * data they carry. In Rock-paper-scissors each player picks one of three hands every round.
+ *
+ * Both players pick at the same time, so a hand is never made as a plain move. It is sealed with
+ * {"SealMove": {"commitment": <hash_move of the hand>}} and revealed with
+ * {"RevealMove": {"move_type": "Rock", "salt": <salt>}} once both players have sealed theirs.
+ *
+ */
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub enum MoveType {
+    Rock,
+    Paper,
+    Scissors,
+}
+
+impl MoveType {
+	pub fn describe() -> Vec<MoveType> {
+		vec![
+			MoveType::Rock,
+			MoveType::Paper,
+			MoveType::Scissors,
+		]
+	}
+
+    pub fn beats(&self, other: &MoveType) -> bool {
+        match (self, other) {
+            (MoveType::Rock, MoveType::Scissors) => true,
+            (MoveType::Paper, MoveType::Rock) => true,
+            (MoveType::Scissors, MoveType::Paper) => true,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+use hdk::holochain_persistence_api::cas::content::Address;
+use hdk::AGENT_ADDRESS;
+
+use crate::game_move::{Move, Outcome};
+use crate::game::Game;
+use super::MoveType;
+
+/**
+ *
+ * The number of rounds is chosen when the game is created, e.g. {"best_of": 5}.
+ * A tied round doesn't count towards the total, so it must be odd for there to always be a winner.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub best_of: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            best_of: 3,
+        }
+    }
+}
+
+impl Settings {
+    pub fn is_valid(&self) -> Result<(), String> {
+        if self.best_of % 2 == 0 {
+            Err("The game must be the best of an odd number of rounds".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The number of rounds a player has to win to win the game
+    pub fn wins_needed(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+}
+
+/**
+ *
+ * Moves are applied a round at a time, so along with the revealed moves of each completed round the
+ * state keeps the hands played in it and who won.
+ *
+ */
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub struct Round {
+    pub player_1: MoveType,
+    pub player_2: MoveType,
+    pub winner: Option<Address>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct GameState {
+    pub settings: Settings,
+    pub moves: Vec<Move>,
+    pub player_1: Address,
+    pub player_2: Address,
+    pub rounds: Vec<Round>,
+}
+
+impl GameState {
+    pub fn initial(game: &Game) -> Self {
+        GameState {
+            settings: game.settings(),
+            moves: Vec::new(),
+            player_1: game.players[0].clone(),
+            player_2: game.players[1].clone(),
+            rounds: Vec::new(),
+        }
+    }
+
+    pub fn wins(&self, player: &Address) -> u32 {
+        self.rounds.iter().filter(|round| round.winner.as_ref() == Some(player)).count() as u32
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        let wins_needed = self.settings.wins_needed();
+        if self.wins(&self.player_1) >= wins_needed {
+            Outcome::Winner(self.player_1.clone())
+        } else if self.wins(&self.player_2) >= wins_needed {
+            Outcome::Winner(self.player_2.clone())
+        } else {
+            Outcome::InProgress
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut disp = "\n".to_string();
+        let me: Address = AGENT_ADDRESS.to_string().into();
+
+        if self.outcome().is_over() {
+            disp.push_str("The game has ended \n");
+        } else {
+            disp.push_str(&format!("Round {}, both players pick a hand at the same time \n", self.rounds.len() + 1));
+        }
+        disp.push_str(&format!("Best of {}\n\n", self.settings.best_of));
+
+        let hands = |round: &Round| if me == self.player_2 {
+            (round.player_2, round.player_1)
+        } else {
+            (round.player_1, round.player_2)
+        };
+        for (i, round) in self.rounds.iter().enumerate() {
+            let result = match &round.winner {
+                None => "tie",
+                Some(winner) if winner == &me => "you won",
+                Some(_) if me == self.player_1 || me == self.player_2 => "you lost",
+                Some(winner) if winner == &self.player_1 => "player 1 won",
+                Some(_) => "player 2 won",
+            };
+            let (my_hand, their_hand) = hands(round);
+            disp.push_str(&format!("{:>3}. {:?} vs {:?} - {}\n", i + 1, my_hand, their_hand, result));
+        }
+
+        let (mine, theirs) = if me == self.player_2 { (&self.player_2, &self.player_1) } else { (&self.player_1, &self.player_2) };
+        disp.push_str(&format!("\nScore: {} - {}\n", self.wins(mine), self.wins(theirs)));
+        disp
+    }
+
+    /// Play a round once both hands are revealed. The moves are in the order of the players in the game.
+    pub fn evolve_round(&self, moves: &[(Move, MoveType)]) -> Self {
+        let mut state = self.clone();
+        let hand_of = |player: &Address| moves.iter().find(|(next_move, _)| &next_move.author == player).map(|(_, hand)| *hand);
+        if let (Some(player_1), Some(player_2)) = (hand_of(&self.player_1), hand_of(&self.player_2)) {
+            let winner = if player_1.beats(&player_2) {
+                Some(self.player_1.clone())
+            } else if player_2.beats(&player_1) {
+                Some(self.player_2.clone())
+            } else {
+                None
+            };
+            state.rounds.push(Round{player_1, player_2, winner});
+        }
+        state.moves.extend(moves.iter().map(|(next_move, _)| next_move.clone()));
+        state
+    }
+}
@@ -0,0 +1,27 @@
+use hdk::holochain_persistence_api::{
+    cas::content::Address,
+};
+
+use crate::game::Game;
+use super::{
+    GameState,
+    MoveType,
+};
+
+
+/**
+ *
+ * A hand is checked when its sealed move is revealed. The framework already makes sure each player
+ * seals and reveals exactly one move per round, so there are no turns to check.
+ *
+ */
+
+
+impl MoveType {
+    pub fn is_valid(&self, _game: Game, _game_state: GameState, _author: &Address) -> Result<(), String> {
+        match self {
+            // any hand can be played in any round
+            MoveType::Rock | MoveType::Paper | MoveType::Scissors => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::convert::TryFrom;
+use hdk::{
+    entry_definition::ValidatingEntryType,
+    error::ZomeApiResult,
+    holochain_persistence_api::{
+        cas::content::{AddressableContent, Address},
+    },
+    holochain_json_api::{
+        error::JsonError, json::JsonString,
+    },
+    holochain_core_types::{
+        dna::entry_types::Sharing,
+        validation::{EntryValidationData, LinkValidationData},
+        entry::Entry,
+        link::LinkMatch,
+    }
+};
+
+use crate::game::{Game, get_game_local_chain, get_moves_local_chain};
+use crate::game_move::{Move, MoveType, MetaMove};
+use crate::game_type::{GameRules, MetaState, reduce_state};
+
+/// A snapshot of the state is committed after every this many moves
+pub const SNAPSHOT_INTERVAL: usize = 20;
+
+/**
+ *
+ * Every move is validated by reducing the state of the game from its moves, so without snapshots each move
+ * replays the whole game before it. A snapshot holds the state after a number of moves so the state can be
+ * reduced from the latest snapshot instead. A snapshot is validated by reducing the state from the snapshot
+ * before it, so a snapshot that made it onto the DHT can be trusted as much as the moves it was made from.
+ *
+ * A snapshot saves applying the rules to the moves before it, not loading them. The moves are left out of the
+ * stored state, as each snapshot would otherwise repeat every move before it, and are put back from the moves
+ * of the game when the state is restored. Those are needed anyway to tell which moves a snapshot was taken of.
+ * Where a game state keeps its moves is up to the game, see `GameRules::strip_moves`.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+pub struct Snapshot {
+    pub game: Address,
+    /// The number of moves the state was reduced from
+    pub move_count: u32,
+    /// The address of the last of those moves
+    pub last_move: Address,
+    pub state: serde_json::Value,
+    pub state_hash: Address,
+}
+
+impl Snapshot {
+    pub fn new<G: GameRules>(game_address: &Address, moves: &[Move], state: &MetaState<G::State>) -> Result<Self, String> {
+        let last_move = moves.last().ok_or("A snapshot must be taken after at least one move")?;
+        let state = stored_state::<G>(state)?;
+        Ok(Snapshot {
+            game: game_address.clone(),
+            move_count: moves.len() as u32,
+            last_move: Entry::App("move".into(), last_move.clone().into()).address(),
+            state_hash: state_hash(&state),
+            state,
+        })
+    }
+
+    /// Whether the snapshot was taken of these moves, the moves of the game up to and past the snapshot
+    fn is_of(&self, moves: &[Move]) -> bool {
+        let move_count = self.move_count as usize;
+        move_count > 0 && move_count <= moves.len()
+            && Entry::App("move".into(), moves[move_count - 1].clone().into()).address() == self.last_move
+            && state_hash(&self.state) == self.state_hash
+    }
+
+    /// The state the snapshot was taken of, with its moves put back from the moves of the game
+    fn restore<G: GameRules>(&self, game: &Game, moves: &[Move]) -> Result<MetaState<G::State>, String> {
+        let moves = &moves[..self.move_count as usize];
+        let mut state = self.state.clone();
+        if let Some(meta_moves) = state.get_mut("moves") {
+            *meta_moves = serde_json::to_value(moves).map_err(|e| e.to_string())?;
+        }
+        if let Some(game_state) = state.get_mut("game_state") {
+            *game_state = G::restore_moves(game_state.take(), &rules_moves(game, moves))?;
+        }
+        serde_json::from_value(state).map_err(|e| format!("Could not read the state of a snapshot: {}", e))
+    }
+}
+
+/// The state as it is stored in a snapshot, without the moves of the game or the moves the game state keeps
+pub fn stored_state<G: GameRules>(state: &MetaState<G::State>) -> Result<serde_json::Value, String> {
+    let mut state = serde_json::to_value(state).map_err(|e| e.to_string())?;
+    if let Some(meta_moves) = state.get_mut("moves") {
+        *meta_moves = serde_json::Value::Array(Vec::new());
+    }
+    if let Some(game_state) = state.get_mut("game_state") {
+        *game_state = G::strip_moves(game_state.take());
+    }
+    Ok(state)
+}
+
+/// The moves the game rules were given, which `GameRules::restore_moves` puts back into the game state. These
+/// are the game moves as made by each seat, or in simultaneous games the moves revealed in each completed round
+/// in the order of the players.
+fn rules_moves(game: &Game, moves: &[Move]) -> Vec<Move> {
+    let mut rules_moves = Vec::new();
+    let mut round: Vec<Move> = Vec::new();
+    for next_move in moves.iter().map(|next_move| game.seated(next_move)) {
+        match next_move.move_type {
+            MoveType::Game(_) => rules_moves.push(next_move),
+            MoveType::Meta(MetaMove::RevealMove{..}) => {
+                round.push(next_move);
+                if game.players.iter().all(|player| round.iter().any(|revealed| &revealed.author == player)) {
+                    for player in game.players.iter() {
+                        rules_moves.extend(round.iter().filter(|revealed| &revealed.author == player).cloned());
+                    }
+                    round = Vec::new();
+                }
+            },
+            MoveType::Meta(_) => {},
+        }
+    }
+    rules_moves
+}
+
+pub fn state_hash(state: &serde_json::Value) -> Address {
+    Entry::App("snapshot_state".into(), JsonString::from(state.clone())).address()
+}
+
+/// Reduce the moves into the state they produce, starting from the latest snapshot taken of them if there is one
+pub fn reduce_from_snapshots<G: GameRules>(game: &Game, snapshots: &[Snapshot], moves: &[Move]) -> Result<MetaState<G::State>, String> {
+    let latest = snapshots.iter()
+        .filter(|snapshot| snapshot.is_of(moves))
+        .max_by_key(|snapshot| snapshot.move_count);
+    match latest {
+        Some(snapshot) => {
+            let state = snapshot.restore::<G>(game, moves)?;
+            moves[snapshot.move_count as usize..].iter().try_fold(state, |state, next_move| {
+                state.evolve::<G>(game, next_move)
+            })
+        },
+        None => reduce_state::<G>(game, moves),
+    }
+}
+
+/*=====================================
+=            DHT Functions            =
+=====================================*/
+
+/// Take a snapshot once the moves reach a multiple of SNAPSHOT_INTERVAL
+pub fn commit_snapshot_if_due<G: GameRules>(game_address: &Address, game: &Game, moves: &[Move]) -> ZomeApiResult<()> {
+    if moves.is_empty() || moves.len() % SNAPSHOT_INTERVAL != 0 {
+        return Ok(())
+    }
+    let snapshots = get_snapshots(game_address)?;
+    let state = reduce_from_snapshots::<G>(game, &snapshots, moves)?;
+    let snapshot = Snapshot::new::<G>(game_address, moves, &state)?;
+    let move_count = snapshot.move_count.to_string();
+    let snapshot_address = hdk::commit_entry(&Entry::App("snapshot".into(), snapshot.into()))?;
+    hdk::link_entries(game_address, &snapshot_address, "game->snapshot", &move_count)?;
+    Ok(())
+}
+
+pub fn get_snapshots(game_address: &Address) -> ZomeApiResult<Vec<Snapshot>> {
+    hdk::utils::get_links_and_load_type(game_address, LinkMatch::Exactly("game->snapshot"), LinkMatch::Any)
+}
+
+/*=====  End of DHT Functions  ======*/
+
+
+/*=============================================
+=            Local chain functions            =
+=============================================*/
+
+pub fn get_snapshots_local_chain(local_chain: &[Entry], game_address: &Address) -> Vec<Snapshot> {
+    local_chain
+        .iter()
+        .filter_map(|entry| {
+            match entry {
+                Entry::App(entry_type, entry_data) if entry_type.to_string() == "snapshot" => {
+                    Snapshot::try_from(entry_data.clone()).ok()
+                },
+                _ => None,
+            }
+        })
+        .filter(|snapshot| &snapshot.game == game_address)
+        .collect()
+}
+
+/*=====  End of Local chain functions  ======*/
+
+
+pub fn definition() -> ValidatingEntryType {
+    entry!(
+        name: "snapshot",
+        description: "The state of a game after a number of its moves",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::ChainFull
+        },
+
+        validation: | validation_data: hdk::EntryValidationData<Snapshot>| {
+            match validation_data {
+                EntryValidationData::Create{entry, validation_data} => {
+                    let mut local_chain = validation_data.package.source_chain_entries
+                        .ok_or("Could not retrieve source chain")?;
+                    let snapshot: Snapshot = entry;
+                    local_chain.remove_item(&Entry::App("snapshot".into(), snapshot.clone().into()));
+
+                    let moves = get_moves_local_chain(local_chain.clone(), &snapshot.game)
+                        .map_err(|_| "Could not load moves during validation")?;
+                    let game = get_game_local_chain(local_chain.clone(), &snapshot.game)
+                        .map_err(|_| "Could not load game during validation")?;
+                    let move_count = snapshot.move_count as usize;
+                    if move_count == 0 || move_count > moves.len() {
+                        return Err("A snapshot must be taken of moves that have been made.".into())
+                    }
+                    let moves = &moves[..move_count];
+                    if Entry::App("move".into(), moves[move_count - 1].clone().into()).address() != snapshot.last_move {
+                        return Err("The last move of a snapshot must be the move at its move count.".into())
+                    }
+                    if state_hash(&snapshot.state) != snapshot.state_hash {
+                        return Err("The state hash does not match the state of the snapshot.".into())
+                    }
+
+                    // only the snapshots before this one can be used to check it
+                    let earlier: Vec<Snapshot> = get_snapshots_local_chain(&local_chain, &snapshot.game).into_iter()
+                        .filter(|earlier| earlier.move_count < snapshot.move_count)
+                        .collect();
+                    dispatch!(game.game_type, G => {
+                        let state = reduce_from_snapshots::<G>(&game, &earlier, moves)?;
+                        if stored_state::<G>(&state)? != snapshot.state {
+                            return Err("The state of the snapshot is not the state its moves produce.".into())
+                        }
+                        Ok(())
+                    })
+                },
+                _ => {
+                    Err("Cannot modify or delete a snapshot".into())
+                }
+            }
+        },
+
+        links: [
+            from!(
+                "game",
+                link_type: "game->snapshot",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData| {
+                    match validation_data {
+                        LinkValidationData::LinkAdd{link, ..} => {
+                            let link = link.link();
+                            let snapshot: Snapshot = hdk::utils::get_as_type(link.target().clone())?;
+                            if &snapshot.game != link.base() {
+                                Err("A snapshot can only be linked from its own game.".into())
+                            } else if link.tag() != &snapshot.move_count.to_string() {
+                                Err("A snapshot must be linked with its move count as the tag.".into())
+                            } else {
+                                Ok(())
+                            }
+                        },
+                        LinkValidationData::LinkRemove{..} => {
+                            Err("Cannot remove the link to a snapshot".into())
+                        },
+                    }
+                }
+            )
+        ]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_move::Secret;
+    use crate::game_type::GameType;
+    use serde_json::json;
+
+    fn game(game_type: GameType, settings: serde_json::Value) -> Game {
+        Game {
+            game_type,
+            settings,
+            players: vec!["alice".into(), "bob".into()],
+            teams: Vec::new(),
+            time_control: None,
+            rated: false,
+            proposal: None,
+            created_at: 0,
+        }
+    }
+
+    /// A few moves of each game, played by bob and alice in turn starting with bob
+    fn moves(game: &Game) -> Vec<Move> {
+        let (alice, bob): (Address, Address) = ("alice".into(), "bob".into());
+        let fleet = json!([{"x": 0, "y": 0, "length": 2, "orientation": "Horizontal"}]);
+        let fleet_commitment = |author: &Address| json!({"Commit": {"key": "fleet", "commitment": Secret{key: "fleet".into(), data: fleet.clone(), salt: author.to_string()}.commitment()}});
+        let seal = |author: &Address, round: u32, hand: &str| json!({"SealMove": {"commitment": Secret::sealed_move(author, round, &json!(hand), "salt").commitment()}});
+        let reveal = |hand: &str| json!({"RevealMove": {"move_type": hand, "salt": "salt"}});
+        let seed = |author: &Address| json!({"RandomnessRequest": {"key": "roll", "commitment": Secret::seed("roll", &author.to_string(), "salt").commitment()}});
+        let seed_reveal = |author: &Address| json!({"RandomnessReveal": {"key": "roll", "seed": author.to_string(), "salt": "salt"}});
+        let move_types = match game.game_type {
+            GameType::TicTacToe => vec![
+                (&bob, json!({"Place": {"pos": {"x": 0, "y": 0}}})),
+                (&alice, json!({"Place": {"pos": {"x": 1, "y": 1}}})),
+            ],
+            GameType::Checkers => vec![
+                (&bob, json!({"MovePiece": {"from": {"x": 1, "y": 5}, "to": {"x": 2, "y": 4}}})),
+                (&alice, json!({"MovePiece": {"from": {"x": 4, "y": 2}, "to": {"x": 3, "y": 3}}})),
+            ],
+            GameType::Chess => vec![
+                (&bob, json!({"MovePiece": {"from": {"x": 4, "y": 1}, "to": {"x": 4, "y": 3}, "promotion": null}})),
+                (&alice, json!({"MovePiece": {"from": {"x": 4, "y": 6}, "to": {"x": 4, "y": 4}, "promotion": null}})),
+            ],
+            GameType::Go => vec![
+                (&bob, json!({"Place": {"pos": {"x": 2, "y": 2}}})),
+                (&alice, json!({"Place": {"pos": {"x": 6, "y": 6}}})),
+            ],
+            GameType::Battleship => vec![
+                (&bob, fleet_commitment(&bob)),
+                (&alice, fleet_commitment(&alice)),
+                (&bob, json!({"Fire": {"x": 0, "y": 0}})),
+                (&alice, json!({"Report": {"result": "Hit"}})),
+            ],
+            GameType::RockPaperScissors => vec![
+                (&bob, seal(&bob, 0, "Rock")),
+                (&alice, seal(&alice, 0, "Paper")),
+                (&alice, reveal("Paper")),
+                (&bob, reveal("Rock")),
+            ],
+            GameType::Pig => vec![
+                (&bob, seed(&bob)),
+                (&alice, seed(&alice)),
+                (&alice, seed_reveal(&alice)),
+                (&bob, seed_reveal(&bob)),
+                (&bob, json!("Hold")),
+            ],
+        };
+        move_types.into_iter().enumerate().map(|(i, (author, move_type))| Move {
+            game: "game".into(),
+            author: author.clone(),
+            move_type: serde_json::from_value(move_type).unwrap(),
+            previous_move: format!("move {}", i).into(),
+            timestamp: i as u32,
+        }).collect()
+    }
+
+    fn assert_round_trip<G: GameRules>(game: &Game) {
+        let moves = moves(game);
+        let state = reduce_state::<G>(game, &moves).unwrap();
+        let snapshot = Snapshot::new::<G>(&"game".into(), &moves, &state).unwrap();
+        assert!(!snapshot.state.to_string().contains("previous_move"), "{:?} stored its moves in a snapshot", game.game_type);
+        let restored = snapshot.restore::<G>(game, &moves).unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), serde_json::to_value(&state).unwrap(), "{:?} was not restored", game.game_type);
+    }
+
+    #[test]
+    fn every_game_type_is_restored_from_a_snapshot() {
+        for game_type in GameType::all() {
+            let settings = match game_type {
+                GameType::Battleship => json!({"board_size": 4, "fleet": [2]}),
+                _ => serde_json::Value::Null,
+            };
+            let game = game(game_type.clone(), settings);
+            dispatch!(game_type, G => assert_round_trip::<G>(&game));
+        }
+    }
+}
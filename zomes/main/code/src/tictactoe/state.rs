@@ -1,9 +1,12 @@
 use hdk::holochain_json_api::{
     error::JsonError, json::JsonString,
 };
-use hdk::AGENT_ADDRESS;
+use hdk::{
+    AGENT_ADDRESS,
+    holochain_persistence_api::cas::content::Address,
+};
 
-use crate::game_move::Move;
+use crate::game_move::{Move, Outcome};
 use crate::game::Game;
 use super::{
     moves::Piece,
@@ -11,10 +14,44 @@ use super::{
     validation::{Player, get_current_player},
 };
 
-pub const BOARD_SIZE: usize = 3;
 pub const PLAYER_1_MARK: char = 'O';
 pub const PLAYER_2_MARK: char = 'X';  //player 2 / Xs go first
 pub const EMPTY_SPACE: char = ' ';
+pub const MAX_BOARD_SIZE: usize = 25;
+
+/**
+ *
+ * The size of the board and how many marks in a row are needed to win are chosen when the game is created.
+ * They default to the classic 3x3 board with 3 in a row, but e.g. {"board_size": 15, "win_length": 5} plays gomoku.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub board_size: usize,
+    pub win_length: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            board_size: 3,
+            win_length: 3,
+        }
+    }
+}
+
+impl Settings {
+    pub fn is_valid(&self) -> Result<(), String> {
+        if self.board_size == 0 || self.board_size > MAX_BOARD_SIZE {
+            Err(format!("Board size must be between 1 and {}", MAX_BOARD_SIZE))
+        } else if self.win_length == 0 || self.win_length > self.board_size {
+            Err("Win length must be between 1 and the board size".into())
+        } else {
+            Ok(())
+        }
+    }
+}
 
 /**
  *
@@ -29,134 +66,127 @@ pub const EMPTY_SPACE: char = ' ';
 
 #[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
 pub struct GameState {
+    pub settings: Settings,
     pub moves: Vec<Move>,
     pub player_1: PlayerState,
     pub player_2: PlayerState,
+    pub outcome: Outcome,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
 pub struct PlayerState {
     pub pieces: Vec<Piece>,
-    pub resigned: bool,
-    pub winner: bool,
 }
 
 impl PlayerState {
     pub fn initial() -> Self {
         PlayerState {
             pieces: Vec::new(),
-            resigned: false,
-            winner: false,
         }
     }
 }
 
 impl GameState {
-    pub fn initial() -> Self {
+    pub fn initial(settings: Settings) -> Self {
         GameState {
+            settings,
             moves: Vec::new(),
             player_1: PlayerState::initial(),
             player_2: PlayerState::initial(),
+            outcome: Outcome::InProgress,
         }
     }
 
-    pub fn render(&self) -> String {
+    /// The players take turns in order. By convention player 2 makes the first move thus accepting the invitation to play
+    pub fn to_move(&self, game: &Game) -> Address {
+        match self.moves.last() {
+            Some(last_move) => game.next_player(&last_move.author),
+            None => game.first_player(),
+        }
+    }
+
+    pub fn render(&self, game: &Game) -> String {
         let mut disp = "\n".to_string();
 
-        if let Some(last_move) = self.moves.last() {
+        if self.outcome.is_over() {
+            disp.push_str("The game has ended \n");
+        } else if let Some(last_move) = self.moves.last() {
             if last_move.author.to_string() == AGENT_ADDRESS.to_string() {
                 disp.push_str("It is your opponents turn \n");
             } else {
                 disp.push_str("It is your turn \n");
             }
+        } else if self.to_move(game) == game.seat_of(&AGENT_ADDRESS.to_string().into()) {
+            disp.push_str("You move first \n");
         } else {
-            disp.push_str("Non-creator must make the first move \n");        
+            disp.push_str("Your opponent moves first \n");
         }
         disp.push('\n');
-        disp.push_str("  x  0 1 2\ny\n");
+
+        // width of the widest coordinate so larger boards stay aligned
+        let size = self.settings.board_size;
+        let w = (size - 1).to_string().len();
+        let header: String = (0..size).map(|x| format!("{:<w$} ", x, w = w)).collect();
+        disp.push_str(&format!("{:w$}  x  {}\ny\n", "", header.trim_end(), w = w - 1));
         let board = board_sparse_to_dense(self);
-        for y in 0..BOARD_SIZE {
-            disp.push_str(&format!("{}   |", y));
-            for x in 0..BOARD_SIZE {
-                let c = match board[x][y] {
+        for y in 0..size {
+            disp.push_str(&format!("{:<w$}   |", y, w = w));
+            for column in board.iter() {
+                let c = match column[y] {
                     1 => PLAYER_1_MARK,
                     2 => PLAYER_2_MARK,
                     _ => EMPTY_SPACE,
                 };
-                disp.push_str(&format!("{}|", c));
+                disp.push_str(&format!("{:^w$}|", c, w = w));
             }
             disp.push('\n');
         }
-
-        if self.player_1.resigned {
-            disp.push_str(&format!("Game over: Player 1 has resigned!\n"));
-        } else if self.player_2.resigned {
-            disp.push_str(&format!("Game over: Player 2 has resigned!\n"));
-        } else if self.player_1.winner {
-            disp.push_str(&format!("Game over: Player 1 is the winner!\n"));
-        } else if self.player_2.winner {
-            disp.push_str(&format!("Game over: Player 2 is the winner!\n"));
-        }
         disp
     }
 
-    pub fn evolve(&self, game: Game, next_move: &Move) -> Self {
-        let current_player = get_current_player(&game, &next_move.author).unwrap();
+    pub fn evolve(&self, game: Game, next_move: &Move, move_type: &MoveType) -> Result<Self, String> {
+        let current_player = get_current_player(&game, &next_move.author)?;
 
-        match &next_move.move_type {
+        match move_type {
             MoveType::Place{pos} => {
-                let mut board = board_sparse_to_dense(&self);
+                let mut board = board_sparse_to_dense(self);
                 let mut moves = self.moves.clone();
                 moves.push(next_move.to_owned());
 
                 // make the move by adding a new piece at the position
-                board[pos.x][pos.y] = match current_player { Player::Player1 => 1, Player::Player2 => 2};
-
-                // check if this resulted in a player victory
-                let mut diag_down = 0;
-                let mut diag_up = 0;
-                let mut across = [0; 3];
-                let mut down = [0; 3];
-                for x in 0..BOARD_SIZE {
-                    for y in 0..BOARD_SIZE {
-                        let delta = match board[x][y] {1 => 1, 2 => -1, _ => 0};
-                        down[x] += delta;
-                        across[y] += delta;
-                        // diag down e.g. \
-                        if x == y {
-                            diag_down += delta;
-                        } //diag up  e.g. /
-                        else if x == (BOARD_SIZE - 1 - y) {
-                            diag_up += delta;
-                        }
-                    }
-                }
-                let player_1_victory = across.iter().any(|e| *e == (BOARD_SIZE as i32)) 
-                                    || down.iter().any(|e| *e == (BOARD_SIZE as i32));
-                                    || diag_down == (BOARD_SIZE as i32);
-                                    || diag_up == (BOARD_SIZE as i32);
-
-                let player_2_victory = across.iter().any(|e| *e == (-1*BOARD_SIZE as i32)) 
-                                    || down.iter().any(|e| *e == (-1*BOARD_SIZE as i32));
-                                    || diag_down == (-1*BOARD_SIZE as i32);
-                                    || diag_up == (-1*BOARD_SIZE as i32);
+                let mark = match current_player { Player::Player1 => 1, Player::Player2 => 2};
+                board[pos.x][pos.y] = mark;
+
+                // check if this resulted in a player victory. Only lines through the new piece can have been completed.
+                let victory = is_winning_piece(&board, pos, self.settings.win_length);
+                let player_1_victory = victory && mark == 1;
+                let player_2_victory = victory && mark == 2;
 
                 let (player_1_pieces, player_2_pieces) = board_dense_to_sparse(board);
 
-                GameState{
+                // a full board without a line is a draw
+                let board_full = player_1_pieces.len() + player_2_pieces.len() == self.settings.board_size * self.settings.board_size;
+                let outcome = if player_1_victory {
+                    Outcome::Winner(game.players[0].clone())
+                } else if player_2_victory {
+                    Outcome::Winner(game.players[1].clone())
+                } else if board_full {
+                    Outcome::Draw
+                } else {
+                    Outcome::InProgress
+                };
+
+                Ok(GameState{
                     player_1: PlayerState {
                         pieces: player_1_pieces,
-                        resigned: false,
-                        winner: player_1_victory,
                     },
                     player_2: PlayerState {
                         pieces: player_2_pieces,
-                        resigned: false,
-                        winner: player_2_victory,
                     },
                     moves,
+                    outcome,
                     ..self.clone()
-                }        
+                })
             }
         }
     }
@@ -167,8 +197,8 @@ impl GameState {
 =            Helper functions            =
 ========================================*/
 
-pub fn board_sparse_to_dense(state: &GameState)-> [[u8; 8]; 8] {
-    let mut board = [[0u8; 8]; 8];
+pub fn board_sparse_to_dense(state: &GameState)-> Vec<Vec<u8>> {
+    let mut board = vec![vec![0u8; state.settings.board_size]; state.settings.board_size];
     state.player_1.pieces.iter().for_each(|piece| {
         board[piece.x][piece.y] = 1;
     });
@@ -178,7 +208,7 @@ pub fn board_sparse_to_dense(state: &GameState)-> [[u8; 8]; 8] {
     board
 }
 
-pub fn board_dense_to_sparse(board: [[u8; 8]; 8]) -> (Vec<Piece>, Vec<Piece>) {
+pub fn board_dense_to_sparse(board: Vec<Vec<u8>>) -> (Vec<Piece>, Vec<Piece>) {
     let mut player_1_pieces = Vec::new();
     let mut player_2_pieces = Vec::new();
     board.iter().enumerate().for_each(|(x, row)| {
@@ -193,4 +223,19 @@ pub fn board_dense_to_sparse(board: [[u8; 8]; 8]) -> (Vec<Piece>, Vec<Piece>) {
     (player_1_pieces, player_2_pieces)
 }
 
+/// True if the piece at pos is part of a row, column or diagonal of at least win_length equal marks
+pub fn is_winning_piece(board: &[Vec<u8>], pos: &Piece, win_length: usize) -> bool {
+    let mark = board[pos.x][pos.y];
+    let size = board.len() as i64;
+    let same_mark = |x: i64, y: i64| x >= 0 && y >= 0 && x < size && y < size && board[x as usize][y as usize] == mark;
+
+    // across, down, diag down e.g. \ and diag up e.g. /
+    [(1, 0), (0, 1), (1, 1), (1, -1)].iter().any(|(dx, dy)| {
+        let count_from = |sign: i64| {
+            (1..).take_while(|step| same_mark(pos.x as i64 + sign * step * dx, pos.y as i64 + sign * step * dy)).count()
+        };
+        1 + count_from(1) + count_from(-1) >= win_length
+    })
+}
+
 /*=====  End of Helper functions  ======*/
@@ -3,18 +3,19 @@ use hdk::holochain_persistence_api::{
 };
 
 use crate::game::Game;
-use crate::game_move::Move;
+use crate::game_type::is_players_turn;
 use super::{
+    TicTacToe,
     GameState,
     moves::Piece,
     MoveType,
-    state::{board_sparse_to_dense, BOARD_SIZE},
+    state::board_sparse_to_dense,
 };
 
 
 /**
  *
- * To implement your own custom rule validation all you need to do is re-implement the function `is_valid` on `Move`
+ * To implement your own custom rule validation all you need to do is re-implement the function `is_valid` on `MoveType`
  * 
  * This function  takes the current game and the game state (which includes all the existing moves) 
  * and determines if a new candidate move is valid. Typically this will involve first matching on the move type
@@ -26,14 +27,14 @@ use super::{
  */
 
 
-impl Move {
-    pub fn is_valid(&self, game: Game, game_state: GameState) -> Result<(), String> {
+impl MoveType {
+    pub fn is_valid(&self, game: Game, game_state: GameState, author: &Address) -> Result<(), String> {
         hdk::debug(format!("{:?}", game_state)).unwrap();
-        // let current_player = get_current_player(&game, &self.author)?;
-        match &self.move_type {
+        // let current_player = get_current_player(&game, author)?;
+        match self {
             MoveType::Place{pos} => {
-                is_players_turn(self.author.clone(), game, &game_state)?;
-                pos.is_in_bounds()?;
+                is_players_turn::<TicTacToe>(&game, &game_state, author)?;
+                pos.is_in_bounds(&game_state)?;
                 pos.is_empty(&game_state)?;
                 hdk::debug("Validation Success!").unwrap();
                 Ok(())            }
@@ -52,40 +53,18 @@ pub enum Player{
 }
 
 pub fn get_current_player(game: &Game, player_addr: &Address) -> Result<Player, String> {
-    match (player_addr == &game.player_1, player_addr == &game.player_2) {
-        (true, true) => return Err("Player cannot play themselves".into()),
-        (true, false) => Ok(Player::Player1),
-        (false, true) => Ok(Player::Player2),
-        (false, false) => return Err("Player is not part of this game!".into()),
+    match game.player_index(player_addr) {
+        Some(0) => Ok(Player::Player1),
+        Some(1) => Ok(Player::Player2),
+        _ => Err("Player is not part of this game!".into()),
     }
 }
 
 
-fn is_players_turn(player: Address, game: Game, game_state: &GameState) -> Result<(), String> {
-    let moves = &game_state.moves;
-    match moves.last() {
-        Some(last_move) => {
-            if last_move.author == player {
-                Err("It is not this players turn.".into())
-            } else {
-                Ok(())
-            }
-        },
-        None => {
-            // by convention player 2 makes the first move thus accepting the invitation to play
-            if game.player_2 == player {
-                Ok(())
-            } else {
-                Err("Player 2 must make the first move.".into())
-            }
-        },
-    }
-}
-
 impl Piece {
-    fn is_in_bounds(&self) -> Result<(), String> {
-        if self.x < BOARD_SIZE 
-        && self.y < BOARD_SIZE // no need to check > 0 as usize is always positive
+    fn is_in_bounds(&self, game_state: &GameState) -> Result<(), String> {
+        if self.x < game_state.settings.board_size
+        && self.y < game_state.settings.board_size // no need to check > 0 as usize is always positive
         {
             Ok(())
         } else {
@@ -0,0 +1,115 @@
+use hdk::holochain_json_api::{
+    error::JsonError, json::JsonString,
+};
+use hdk::holochain_persistence_api::cas::content::Address;
+
+/**
+ *
+ * A game can be played with a time control, chosen when it is created. With `PerMove` every turn must be
+ * finished within a number of seconds of the previous one. With `Total` each player has a budget of seconds
+ * for the whole game and gets the increment added after each of their turns. Moves that don't end a turn,
+ * such as a draw offer, still move the clocks on but give no time back.
+ *
+ * Time is measured with the timestamps of the moves, which validation checks are in order and not later
+ * than the header of the entry. Clocks only start running once the first move has been made.
+ * A player whose clock has run out doesn't lose straight away, their opponent claims the win with ClaimTimeout.
+ * That needs a single opponent, so only two player games can have a time control.
+ *
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq)]
+pub enum TimeControl {
+    PerMove {
+        seconds: u32,
+    },
+    Total {
+        seconds: u32,
+        increment: u32,
+    },
+}
+
+impl TimeControl {
+    pub fn is_valid(&self) -> Result<(), String> {
+        match self {
+            TimeControl::PerMove{seconds} | TimeControl::Total{seconds, ..} if *seconds == 0 => {
+                Err("A time control must give the players some time to move".into())
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// The seconds on each player's clock at the start of the game
+    pub fn initial_seconds(&self) -> i64 {
+        match self {
+            TimeControl::PerMove{seconds} | TimeControl::Total{seconds, ..} => i64::from(*seconds),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Clock {
+    pub player: Address,
+    /// The seconds the player had left at the time of the last move. Negative once their time has run out.
+    pub remaining: i64,
+}
+
+/*========================================
+=            Helper functions            =
+========================================*/
+
+/// Seconds since the unix epoch of an ISO 8601 date and time such as "2019-07-15T08:46:09+00:00",
+/// the format of the timestamps in chain headers
+pub fn unix_seconds(iso8601: &str) -> Option<i64> {
+    let iso8601 = iso8601.trim();
+    let (date, time) = iso8601.split_at(iso8601.find(&['T', ' '][..])?);
+    let time = &time[1..];
+
+    let mut date_parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date_parts.next()??, date_parts.next()??, date_parts.next()??);
+
+    // the time of day is followed by optional fractions of a second and the offset from UTC
+    let offset_at = time.find(&['Z', '+', '-'][..]).unwrap_or(time.len());
+    let (clock, offset) = time.split_at(offset_at);
+    let clock = clock.split('.').next()?;
+    let mut clock_parts = clock.splitn(3, ':').map(|part| part.parse::<i64>().ok());
+    let (hours, minutes, seconds) = (clock_parts.next()??, clock_parts.next()??, clock_parts.next().unwrap_or(Some(0))?);
+
+    let offset_seconds = match offset.chars().next() {
+        Some(sign) if sign == '+' || sign == '-' => {
+            let digits: String = offset[1..].chars().filter(|c| c.is_ascii_digit()).collect();
+            if digits.len() != 4 {
+                return None
+            }
+            let offset = digits[..2].parse::<i64>().ok()? * 3600 + digits[2..].parse::<i64>().ok()? * 60;
+            if sign == '+' { offset } else { -offset }
+        },
+        _ => 0,
+    };
+
+    Some(days_from_civil(year, month, day) * 86400 + hours * 3600 + minutes * 60 + seconds - offset_seconds)
+}
+
+/// The (year, month, day) of a unix timestamp, see http://howardhinnant.github.io/date_algorithms.html
+pub fn civil_date(unix_seconds: i64) -> (i64, i64, i64) {
+    let days = if unix_seconds >= 0 { unix_seconds } else { unix_seconds - 86399 } / 86400 + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Days since the unix epoch of a civil date, see http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/*=====  End of Helper functions  ======*/
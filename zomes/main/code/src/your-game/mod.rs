@@ -15,3 +15,38 @@ pub use self::{
         MoveType,
     },
 };
+
+
+use crate::game::Game;
+use crate::game_move::Move;
+use crate::game_type::GameRules;
+
+/**
+ * Rename this struct after your game. It is what ties your state, moves and validation into the framework.
+ */
+pub struct YourGame;
+
+impl GameRules for YourGame {
+    type State = GameState;
+    type MoveType = MoveType;
+
+    fn initial(_game: &Game) -> GameState {
+        GameState::initial()
+    }
+
+    fn evolve(game: &Game, state: &GameState, next_move: &Move, move_type: &MoveType) -> Result<GameState, String> {
+        Ok(state.evolve(game.clone(), next_move, move_type))
+    }
+
+    fn is_valid(game: &Game, state: &GameState, next_move: &Move, move_type: &MoveType) -> Result<(), String> {
+        move_type.is_valid(game.clone(), state.clone(), &next_move.author)
+    }
+
+    fn render(_game: &Game, state: &GameState) -> String {
+        state.render()
+    }
+
+    fn describe() -> Vec<MoveType> {
+        MoveType::describe()
+    }
+}
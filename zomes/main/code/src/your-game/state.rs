@@ -45,7 +45,6 @@ pub struct GameState {
  * #[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
  * pub struct PlayerState {
  *     pub pieces: Vec<Piece>,
- *     pub resigned: bool,
  *     pub winner: bool,
  * }
  */
@@ -64,12 +63,10 @@ impl GameState {
          *         moves: Vec::new(),
          *         player_1: PlayerState {
          *             pieces: Vec::new(),
-         *             resigned: false,
          *             winner: false,
          *         },
          *         player_2: PlayerState {
          *             pieces: Vec::new(),
-         *             resigned: false,
          *             winner: false,
          *         },
          *     }
@@ -88,11 +85,11 @@ impl GameState {
          */
     }
 
-    pub fn evolve(&self, game: Game, next_move: &Move) -> Self {
+    pub fn evolve(&self, game: Game, next_move: &Move, move_type: &MoveType) -> Self {
         /**
          * DEVCAMP TODO #6:
          * Return the new game state resulting from applying the next_move to the current state of the game
-         * You can assume that next_move is valid
+         * You can assume that next_move is valid. Its move_type has already been read into your MoveType
          * 
          * Hints: 
          *   - This is similar to a Redux reducer, a function that given a state and a new action,
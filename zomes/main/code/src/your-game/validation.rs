@@ -3,7 +3,6 @@ use hdk::holochain_persistence_api::{
 };
 
 use crate::game::Game;
-use crate::game_move::Move;
 use super::{
     GameState,
     MoveType,
@@ -12,7 +11,7 @@ use super::{
 
 /**
  *
- * To implement your own custom rule validation all you need to do is re-implement the function `is_valid` on `Move`
+ * To implement your own custom rule validation all you need to do is re-implement the function `is_valid` on `MoveType`
  * 
  * This function  takes the current game and the game state (which includes all the existing moves) 
  * and determines if a new candidate move is valid. Typically this will involve first matching on the move type
@@ -24,8 +23,8 @@ use super::{
  */
 
 
-impl Move {
-    pub fn is_valid(&self, game: Game, game_state: GameState) -> Result<(), String> {
+impl MoveType {
+    pub fn is_valid(&self, game: Game, game_state: GameState, author: &Address) -> Result<(), String> {
         /**
          * DEVCAMP TODO #7:
          * Return Ok() if the self move is valid, or Err("Error message".into()) otherwise
@@ -34,7 +33,7 @@ impl Move {
          *   - You can use the standard '?' rust notation to call helper functions and 
          *     return their error messages upwards (akin to 'throw Exception' in other languages)
          *   - Usually you should make global move checks first ('is it the turn of the author of the move?'),
-         *     and then match the 'self.move_type' enum and check the validity of each variant
+         *     and then match on 'self' and check the validity of each variant
          * References: https://doc.rust-lang.org/edition-guide/rust-2018/error-handling-and-panics/the-question-mark-operator-for-easier-error-handling.html
          */
     }